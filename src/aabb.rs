@@ -0,0 +1,174 @@
+// Axis-aligned boxes with inclusive integer bounds.
+use std::{fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::{
+    input::AoCLineInput,
+    pos::{Coord, Vector},
+};
+
+/// Box spanning `min..=max` on every axis. Boxes are never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Aabb<T, const N: usize> {
+    pub min: Vector<T, N>,
+    pub max: Vector<T, N>,
+}
+
+impl<T: Coord + From<i8>, const N: usize> Aabb<T, N> {
+    pub fn new(min: Vector<T, N>, max: Vector<T, N>) -> Self {
+        assert!(
+            min.iter().zip(max.iter()).all(|(lo, hi)| lo <= hi),
+            "empty box"
+        );
+        Self { min, max }
+    }
+
+    pub fn from_ranges(ranges: [(T, T); N]) -> Self {
+        Self::new(
+            Vector(ranges.map(|(lo, _)| lo)),
+            Vector(ranges.map(|(_, hi)| hi)),
+        )
+    }
+
+    pub fn point(p: Vector<T, N>) -> Self {
+        Self { min: p, max: p }
+    }
+
+    /// Number of lattice points along every axis.
+    pub fn size(&self) -> Vector<T, N> {
+        self.max - self.min + Vector::splat(T::from(1))
+    }
+
+    pub fn volume(&self) -> T {
+        self.size().iter().fold(T::from(1), |acc, s| acc * s)
+    }
+
+    pub fn contains(&self, p: &Vector<T, N>) -> bool {
+        (0..N).all(|d| self.min[d] <= p[d] && p[d] <= self.max[d])
+    }
+
+    pub fn contains_box(&self, other: &Self) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (min, max) = (
+            self.min.component_max(&other.min),
+            self.max.component_min(&other.max),
+        );
+        if (0..N).all(|d| min[d] <= max[d]) {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Smallest box containing both boxes.
+    pub fn union_bounds(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.component_min(&other.min),
+            max: self.max.component_max(&other.max),
+        }
+    }
+
+    /// Split `self` minus `other` into at most `2N` disjoint boxes.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(cut) = self.intersection(other) else {
+            return vec![*self];
+        };
+
+        // Peel off a slab below and above `cut` along every axis in turn.
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for d in 0..N {
+            if rest.min[d] < cut.min[d] {
+                let mut below = rest;
+                below.max[d] = cut.min[d] - T::from(1);
+                pieces.push(below);
+                rest.min[d] = cut.min[d];
+            }
+            if rest.max[d] > cut.max[d] {
+                let mut above = rest;
+                above.min[d] = cut.max[d] + T::from(1);
+                pieces.push(above);
+                rest.max[d] = cut.max[d];
+            }
+        }
+        pieces
+    }
+}
+
+impl<T: Display, const N: usize> Display for Aabb<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const AXES: [char; 3] = ['x', 'y', 'z'];
+        let ranges = (0..N).map(|d| match AXES.get(d) {
+            Some(axis) => format!("{}={}..{}", axis, self.min[d], self.max[d]),
+            None => format!("{}..{}", self.min[d], self.max[d]),
+        });
+        write!(f, "{}", ranges.format(","))
+    }
+}
+
+/// Parses `x=a..b,y=c..d` with one range per dimension. Axis names are
+/// ignored and may be omitted.
+impl<T, const N: usize> AoCLineInput for Aabb<T, N>
+where
+    T: Coord + From<i8> + FromStr,
+    T::Err: std::fmt::Debug,
+{
+    fn from_line(s: &str) -> Self {
+        let ranges = s
+            .split(',')
+            .map(|range| {
+                let range = range.trim();
+                let range = range.split_once('=').map_or(range, |(_, r)| r);
+                let (lo, hi) = range.split_once("..").unwrap();
+                (lo.parse().unwrap(), hi.parse().unwrap())
+            })
+            .collect_vec();
+        let len = ranges.len();
+        match ranges.try_into() {
+            Ok(ranges) => Self::from_ranges(ranges),
+            Err(_) => panic!("expected {} ranges, got {} in {:?}", N, len, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Aabb;
+    use crate::input::AoCLineInput;
+
+    #[test]
+    fn test_parse_and_volume() {
+        let b = Aabb::<i64, 3>::from_line("x=10..12,y=10..12,z=-1..1");
+        assert_eq!(b.volume(), 27);
+        assert_eq!(b.to_string(), "x=10..12,y=10..12,z=-1..1");
+        let t = Aabb::<i32, 2>::from_line("x=20..30, y=-10..-5");
+        assert_eq!(t.volume(), 66);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let a = Aabb::<i64, 3>::from_line("x=0..9,y=0..9,z=0..9");
+        let b = Aabb::<i64, 3>::from_line("x=3..5,y=-5..4,z=2..20");
+        let pieces = a.subtract(&b);
+        assert!(pieces.len() <= 6);
+        let cut = a.intersection(&b).unwrap();
+        assert_eq!(
+            pieces.iter().map(|p| p.volume()).sum::<i64>(),
+            a.volume() - cut.volume()
+        );
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(a.contains_box(p));
+            assert!(!p.intersects(&b));
+            assert!(pieces[i + 1..].iter().all(|q| !p.intersects(q)));
+        }
+        assert_eq!(a.subtract(&a), vec![]);
+    }
+}
@@ -0,0 +1,187 @@
+// Cellular automata: every cell of a grid is updated at once from its old
+// value and the old values of its neighbours.
+use crate::{
+    matrix::{Ix, MatGet, MatMut},
+    pos::Dir8,
+};
+
+/// Which neighbours a rule gets to see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// North, east, south and west.
+    Four,
+    /// Also the diagonals.
+    Eight,
+}
+
+impl Neighbourhood {
+    pub fn dirs(self) -> &'static [Dir8] {
+        match self {
+            Neighbourhood::Four => &[Dir8::North, Dir8::East, Dir8::South, Dir8::West],
+            Neighbourhood::Eight => &Dir8::ALL,
+        }
+    }
+}
+
+/// A cell as seen by a rule. Neighbours are looked up with `get_signed`, so
+/// the grid's boundary policy decides what lies beyond its edges. Those that
+/// do not exist, or are not part of the neighbourhood, are `None`.
+#[derive(Debug, Clone)]
+pub struct Cell<T> {
+    pub ix: Ix,
+    pub value: T,
+    neighbours: [Option<T>; 8],
+}
+
+impl<T: Copy> Cell<T> {
+    fn new<'a>(grid: &impl MatGet<'a, T>, ix: Ix, hood: Neighbourhood) -> Self
+    where
+        T: 'a,
+    {
+        let mut neighbours = [None; 8];
+        for &dir in hood.dirs() {
+            let d = dir.delta::<isize>();
+            neighbours[dir as usize] = grid.get_signed((ix.0 as isize + d.y, ix.1 as isize + d.x));
+        }
+        Self {
+            ix,
+            value: grid.get(ix),
+            neighbours,
+        }
+    }
+
+    pub fn neighbour(&self, dir: impl Into<Dir8>) -> Option<T> {
+        self.neighbours[dir.into() as usize]
+    }
+
+    /// Values of all existing neighbours, clockwise from north.
+    pub fn neighbours(&self) -> impl Iterator<Item = T> + '_ {
+        self.neighbours.iter().flatten().copied()
+    }
+}
+
+/// One phase of a multi-phase step.
+pub type Rule<'r, T> = &'r dyn Fn(&Cell<T>) -> T;
+
+/// Steps a grid by a rule, writing into a second buffer that then swaps
+/// places with the grid, so no step needs to allocate.
+#[derive(Debug, Clone)]
+pub struct Automaton<G> {
+    grid: G,
+    back: G,
+}
+
+impl<G: Clone> Automaton<G> {
+    pub fn new(grid: G) -> Self {
+        Self {
+            back: grid.clone(),
+            grid,
+        }
+    }
+
+    pub fn grid(&self) -> &G {
+        &self.grid
+    }
+
+    /// Apply `f` to the grid and the buffer the next step writes into alike,
+    /// e.g. to change the boundary policy. Must not change the grid's size.
+    pub fn reconfigure(&mut self, f: impl Fn(&mut G)) {
+        f(&mut self.grid);
+        f(&mut self.back);
+    }
+
+    pub fn into_grid(self) -> G {
+        self.grid
+    }
+
+    /// Replace every cell by `rule` applied to it. Returns how many cells changed.
+    pub fn step<'g, T>(&mut self, hood: Neighbourhood, rule: impl Fn(&Cell<T>) -> T) -> usize
+    where
+        G: MatGet<'g, T> + for<'a> MatMut<'a, T>,
+        T: Copy + PartialEq + 'g,
+    {
+        let mut changed = 0;
+        for ix in self.grid.iter_idx() {
+            let cell = Cell::new(&self.grid, ix, hood);
+            let next = rule(&cell);
+            if next != cell.value {
+                changed += 1;
+            }
+            *self.back.get_mut(ix) = next;
+        }
+        std::mem::swap(&mut self.grid, &mut self.back);
+        changed
+    }
+
+    /// Run one step per rule, each seeing the result of the previous one.
+    /// Returns how many cells changed over all phases.
+    pub fn step_phases<'g, T>(&mut self, hood: Neighbourhood, phases: &[Rule<T>]) -> usize
+    where
+        G: MatGet<'g, T> + for<'a> MatMut<'a, T>,
+        T: Copy + PartialEq + 'g,
+    {
+        phases.iter().map(|rule| self.step(hood, rule)).sum()
+    }
+
+    /// Call `step` until it reports no changes. Returns the number of calls,
+    /// including the last one.
+    pub fn run_until_stable(&mut self, mut step: impl FnMut(&mut Self) -> usize) -> usize {
+        let mut steps = 1;
+        while step(self) > 0 {
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Automaton, Neighbourhood};
+    use crate::input::{AoCInput, Chars};
+    use crate::matrix::{Mat, Torus};
+    use crate::pos::Dir4;
+
+    #[test]
+    fn test_game_of_life() {
+        let glider = ".#...\n..#..\n###..\n.....\n.....\n";
+        let mut ca = Automaton::new(Chars::<Torus<char>>::from_input(glider).0);
+        let rule =
+            |c: &super::Cell<char>| match (c.value, c.neighbours().filter(|n| *n == '#').count()) {
+                ('#', 2 | 3) | ('.', 3) => '#',
+                _ => '.',
+            };
+        for _ in 0..20 {
+            ca.step(Neighbourhood::Eight, rule);
+        }
+        // A glider on a 5x5 torus is back where it started after 20 steps.
+        assert_eq!(ca.grid().render().to_string(), glider);
+    }
+
+    #[test]
+    fn test_phases_until_stable() {
+        let mut ca = Automaton::new(Chars::<Mat<char>>::from_input(">.>..\n").0);
+        let east = |c: &super::Cell<char>| match c.value {
+            '>' if c.neighbour(Dir4::East) == Some('.') => '.',
+            '.' if c.neighbour(Dir4::West) == Some('>') => '>',
+            v => v,
+        };
+        assert_eq!(ca.step_phases(Neighbourhood::Four, &[&east, &east]), 8);
+        assert_eq!(ca.grid().render().to_string(), "..>.>\n");
+        let steps = ca.run_until_stable(|ca| ca.step(Neighbourhood::Four, east));
+        assert_eq!(steps, 2);
+        assert_eq!(ca.grid().render().to_string(), "...>>\n");
+        assert_eq!(ca.step(Neighbourhood::Four, east), 0);
+    }
+
+    #[test]
+    fn test_borrowed_cells() {
+        // Cells may borrow from data that lives shorter than the program.
+        let words = String::from("ant bee cat");
+        let row = words.split(' ').collect();
+        let mut ca = Automaton::new(Mat::from_rows(vec![row]));
+        ca.step(Neighbourhood::Four, |c: &super::Cell<&str>| {
+            c.neighbour(Dir4::West).unwrap_or(c.value)
+        });
+        assert_eq!(ca.grid().row(0), ["ant", "ant", "bee"]);
+    }
+}
@@ -1,60 +1,43 @@
-use std::collections::HashSet;
-
-use aoc21::matrix::{Ix, Mat, MatGet, MatMut};
+use aoc21::automaton::{Automaton, Neighbourhood::Eight};
+use aoc21::input::Digits;
+use aoc21::matrix::{Mat, MatGet};
 
 aoc21::simple_main!(11);
 
-type Input = Mat<u8>;
+type Input = Digits<Mat<u8>>;
 type Output = usize;
 
+/// Energy level, and whether the octopus flashed during the current step.
+type Octopus = (u8, bool);
+
+fn octopuses(inp: &Input) -> Automaton<Mat<Octopus>> {
+    Automaton::new(inp.map(|e| (*e, false)))
+}
+
 fn part1(inp: &Input) -> Output {
-    let mut mat = inp.clone();
-    (0..100).map(|_| power_up(&mut mat)).sum()
+    let mut ca = octopuses(inp);
+    (0..100).map(|_| power_up(&mut ca)).sum()
 }
 
 fn part2(inp: &Input) -> Output {
-    let mut mat = inp.clone();
-    let mut step = 0;
-    let want = inp.m * inp.n;
-    loop {
-        step += 1;
-        if power_up(&mut mat) == want {
-            break;
-        }
-    }
-    step
+    let mut ca = octopuses(inp);
+    let all = inp.height() * inp.width();
+    (1..).find(|_| power_up(&mut ca) == all).unwrap()
 }
 
-fn power_up(mat: &mut Input) -> usize {
-    let mut flash_stack = Vec::new();
-    let inc = |m: &mut Input, f: &mut Vec<Ix>, pos| {
-        let val = m.get_mut(pos);
-        *val += 1;
-        if *val > 9 {
-            f.push(pos);
-        }
-    };
-
-    mat.clone()
-        .iter_idx()
-        .for_each(|p| inc(mat, &mut flash_stack, p));
-
-    let mut flashed = HashSet::new();
-    while let Some(pos) = flash_stack.pop() {
-        if flashed.contains(&pos) {
-            continue;
-        }
-        flashed.insert(pos);
-        mat.clone().iter_diag_bordering_idx(pos).for_each(|p| {
-            inc(mat, &mut flash_stack, p);
+/// Returns the number of flashes.
+fn power_up(ca: &mut Automaton<Mat<Octopus>>) -> usize {
+    ca.step(Eight, |c| (c.value.0 + 1, false));
+    // Octopuses above 9 that have not flashed yet do so now.
+    ca.run_until_stable(|ca| {
+        ca.step(Eight, |c| {
+            let (energy, flashed) = c.value;
+            let flashing = c.neighbours().filter(|(e, f)| *e > 9 && !f).count();
+            (energy + flashing as u8, flashed || energy > 9)
         })
-    }
-
-    for f in &flashed {
-        *mat.get_mut(*f) = 0;
-    }
-
-    flashed.len()
+    });
+    // Every octopus that flashed changes back to zero.
+    ca.step(Eight, |c| if c.value.1 { (0, false) } else { c.value })
 }
 
 #[allow(dead_code)]
@@ -1,16 +1,20 @@
-use std::collections::HashSet;
-
-use aoc21::{input::AoCInput as _, matrix::Ix};
+use aoc21::{
+    input::AoCInput as _,
+    matrix::{Ix, SparseGrid},
+};
 use itertools::Itertools;
 
 aoc21::main!(13);
 
-type Input = (Vec<Ix>, Vec<(bool, usize)>);
+type Input = (SparseGrid<bool>, Vec<(bool, isize)>);
 type Output = usize;
 
 fn parse(s: &str) -> Input {
     let (dots_str, folds_str) = s.split_once("\n\n").unwrap();
-    let dots: Vec<Ix> = Vec::<Ix>::from_input(dots_str);
+    let dots = Vec::<Ix>::from_input(dots_str)
+        .into_iter()
+        .map(|(x, y)| ((y as isize, x as isize), true))
+        .collect();
     let folds = folds_str
         .lines()
         .map(|line| {
@@ -22,48 +26,32 @@ fn parse(s: &str) -> Input {
 }
 
 fn part1((dots, folds): &Input) -> Output {
-    perform_folds(dots, vec![folder(folds[0])]).len()
-}
-
-fn perform_folds(dots: &Vec<Ix>, folds: Vec<impl Fn(Ix) -> Ix>) -> HashSet<Ix> {
-    let mut dots: HashSet<Ix> = HashSet::from_iter(dots.clone().into_iter());
-    folds.iter().for_each(|f| {
-        dots = HashSet::from_iter(dots.clone().into_iter().map(f));
-    });
-    dots
+    fold(dots, folds[0]).len()
 }
 
-fn folder((fold_x, at): (bool, usize)) -> impl Fn(Ix) -> Ix {
-    move |(x, y)| {
-        if fold_x && x > at {
-            (2 * at - x, y)
-        } else if !fold_x && y > at {
-            (x, 2 * at - y)
-        } else {
-            (x, y)
-        }
-    }
+/// Mirrors every dot past the fold line onto the part before it.
+fn fold(paper: &SparseGrid<bool>, (fold_x, at): (bool, isize)) -> SparseGrid<bool> {
+    paper
+        .iter()
+        .map(|((y, x), _)| {
+            if fold_x && x > at {
+                ((y, 2 * at - x), true)
+            } else if !fold_x && y > at {
+                ((2 * at - y, x), true)
+            } else {
+                ((y, x), true)
+            }
+        })
+        .collect()
 }
 
 fn part2((dots, folds): &Input) -> String {
-    let folders = folds.iter().map(|f| folder(*f)).collect_vec();
-    let dots = perform_folds(dots, folders);
-    let (w, h) = (
-        dots.iter().map(|p| p.0).max().unwrap(),
-        dots.iter().map(|p| p.1).max().unwrap(),
-    );
-    let mut out = String::new();
-    for y in 0..h + 1 {
-        for x in 0..w + 1 {
-            if dots.contains(&(x, y)) {
-                out += "#";
-            } else {
-                out += " ";
-            }
-        }
-        out += "\n";
-    }
-    out
+    let paper = folds.iter().fold(dots.clone(), |p, f| fold(&p, *f));
+    let (_, hi) = paper.bounds().unwrap();
+    paper
+        .window((0, 0), hi)
+        .render_with(|dot| if *dot { '#' } else { ' ' })
+        .to_string()
 }
 
 #[allow(dead_code)]
@@ -94,3 +82,12 @@ aoc21::test_part2!(
     TEST_INPUT,
     "#####\n#   #\n#   #\n#   #\n#####\n".to_string()
 );
+
+#[test]
+fn test_fold_past_last_dot() {
+    let paper: SparseGrid<bool> = [((0, 0), true), ((2, 0), true)].into_iter().collect();
+    assert_eq!(fold(&paper, (false, 5)), paper);
+    let folded = fold(&paper, (false, 1));
+    assert_eq!(folded.len(), 1);
+    assert_eq!(folded.bounds(), Some(((0, 0), (0, 0))));
+}
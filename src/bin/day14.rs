@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
 use aoc21::counter::Counter;
+use aoc21::evolve::Evolution;
 use aoc21::input::sections_tuple;
 use itertools::Itertools;
-use memoize::memoize;
 
 aoc21::main!(14);
 
@@ -26,46 +26,35 @@ fn parse(s: &str) -> Input {
 }
 
 fn part1((formula, ins): &Input) -> Output {
-    let counts = count_all(ins, formula, 10);
-    *counts.max().1 - *counts.min().1
+    spread(&polymer_elements(formula, ins, 10))
 }
 
 fn part2((formula, ins): &Input) -> Output {
-    let counts = count_all(ins, formula, 40);
-    *counts.max().1 - *counts.min().1
+    spread(&polymer_elements(formula, ins, 40))
 }
 
-fn count_all(ins: &Vec<((char, char), char)>, formula: &Vec<char>, depth: usize) -> Counter<char> {
-    let mut count = Counter::new();
-    count.count(formula.iter().cloned());
-    for (&a, &b) in formula.iter().zip(formula.iter().skip(1)) {
-        count.add_from(count_added(ins.clone(), (a, b), depth));
-    }
-    count
+fn spread(counts: &Counter<char>) -> u64 {
+    let (_, most) = counts.max().unwrap();
+    let (_, least) = counts.min().unwrap();
+    most - least
 }
 
-#[memoize]
-fn count_added(
-    ins: Vec<((char, char), char)>,
-    formula @ (a, b): (char, char),
-    depth: usize,
-) -> Counter<char> {
-    let mut counter = Counter::new();
-    if depth == 0 {
-        return counter;
-    }
-
-    let lookup = HashMap::<(char, char), char>::from_iter(ins.clone().into_iter());
-    if let Some(&mid) = lookup.get(&formula) {
-        counter.inc(mid);
+/// Only the number of each adjacent pair matters, not where it is.
+fn polymer_elements(formula: &[char], ins: &[((char, char), char)], steps: usize) -> Counter<char> {
+    let rules: HashMap<(char, char), char> = ins.iter().copied().collect();
+    let mut pairs = Evolution::new(formula.iter().copied().tuple_windows().collect());
+    pairs.run(steps, |&(a, b)| match rules.get(&(a, b)) {
+        Some(&mid) => vec![((a, mid), 1), ((mid, b), 1)],
+        None => vec![((a, b), 1)],
+    });
 
-        if depth > 1 {
-            counter.add_from(count_added(ins.clone(), (a, mid), depth - 1));
-            counter.add_from(count_added(ins, (mid, b), depth - 1));
-        }
+    // Every element starts a pair, except the last one.
+    let mut elements = Counter::new();
+    for (&(a, _), &n) in pairs.live().iter() {
+        elements.add(a, n);
     }
-
-    counter
+    elements.inc(*formula.last().unwrap());
+    elements
 }
 
 #[allow(dead_code)]
@@ -1,42 +1,13 @@
-use std::{
-    collections::{BinaryHeap, HashSet},
-    ops::Deref,
-};
-
-use aoc21::matrix::{Ix, Mat, MatGet};
+use aoc21::input::Digits;
+use aoc21::matrix::{Mat, MatGet};
+use aoc21::search::{self, BucketQueue, DenseIndex};
+use aoc21::view::Tiled;
 
 aoc21::simple_main!(15);
 
-type Input = Mat<u8>;
+type Input = Digits<Mat<u8>>;
 type Output = u32;
 
-struct ExtMat<'a> {
-    mat: &'a Mat<u8>,
-    size_mult: usize,
-}
-
-impl<'a> Deref for ExtMat<'a> {
-    type Target = &'a Mat<u8>;
-    fn deref(&self) -> &Self::Target {
-        &self.mat
-    }
-}
-
-impl<'a> MatGet<'a, u8> for ExtMat<'a> {
-    fn width(&self) -> usize {
-        self.n * self.size_mult
-    }
-
-    fn height(&self) -> usize {
-        self.m * self.size_mult
-    }
-
-    fn get(&self, (i, j): Ix) -> u8 {
-        let (quad_i, quad_j) = ((i / self.n) as u8, (j / self.m) as u8);
-        (self.mat.get((i % self.n, j % self.n)) + quad_i + quad_j - 1) % 9 + 1
-    }
-}
-
 fn part1(mat: &Input) -> Output {
     find_shortest_path(mat, 1)
 }
@@ -45,29 +16,22 @@ fn part2(mat: &Input) -> Output {
     find_shortest_path(mat, 5)
 }
 
-fn find_shortest_path(mat: &Input, size_mult: usize) -> Output {
-    let mat = ExtMat { mat, size_mult };
-
-    let mut queue = BinaryHeap::new();
-    queue.push((0, (0, 0)));
-    let mut seen = HashSet::new();
-    seen.insert((0, 0));
-
-    while let Some((cost, pos)) = queue.pop() {
-        if pos == (mat.height() - 1, mat.width() - 1) {
-            return -cost as u32;
-        }
-
-        for (p, c) in mat.iter_bordering(pos) {
-            if seen.contains(&p) {
-                continue;
-            }
-            seen.insert(p);
-
-            queue.push((cost - c as i32, p));
-        }
-    }
-    panic!("No path found");
+fn find_shortest_path(Digits(mat): &Input, size_mult: usize) -> Output {
+    let mat = Tiled::new(mat, size_mult, size_mult, |risk, (ti, tj)| {
+        (risk + (ti + tj) as u8 - 1) % 9 + 1
+    });
+
+    let (h, w) = (mat.height(), mat.width());
+    let goal = (h - 1, w - 1);
+    // Risks are single digits, so the frontier spans at most ten buckets.
+    let found = search::dijkstra_with(
+        BucketQueue::default(),
+        DenseIndex::new(h * w, |&(i, j): &(usize, usize)| i * w + j),
+        (0, 0),
+        |&pos| mat.iter_bordering(pos).map(|(p, risk)| (p, risk as u32)),
+        |&pos| pos == goal,
+    );
+    found.expect("No path found").cost
 }
 
 #[allow(dead_code)]
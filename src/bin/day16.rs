@@ -1,4 +1,5 @@
-use aoc21::input::AoCInput;
+use aoc21::bits::BitVec;
+use aoc21::input::{AoCInput, HexDigits};
 use itertools::Itertools;
 
 aoc21::simple_main!(16);
@@ -6,23 +7,11 @@ aoc21::simple_main!(16);
 type Input = Packet;
 type Output = usize;
 
-struct Packet(Vec<u8>);
+struct Packet(BitVec);
 
 impl AoCInput for Packet {
     fn from_input(s: &str) -> Self {
-        let cs = s
-            .chars()
-            .map(|c| c.to_digit(16).unwrap() as u8)
-            .collect_vec();
-        let mut bytes = Vec::new();
-        for chunk in cs.chunks(2) {
-            if let [h, l] = chunk {
-                bytes.push((h << 4) | l);
-            } else {
-                bytes.push(chunk[0] << 4);
-            }
-        }
-        Packet(bytes)
+        Packet(HexDigits::<BitVec>::from_input(s).0)
     }
 }
 
@@ -44,28 +33,10 @@ impl SubType {
 type Lit = u64;
 
 impl Packet {
-    pub fn bits(&self, from: usize, len: usize) -> u16 {
-        if len == 0 {
-            panic!("cannot get len 0");
-        }
-        if len > 16 {
-            panic!("requested more than 16 bits");
-        }
-
-        let from_chunk = from / 8;
-        let mut num = (self.0[from_chunk] as u32) << 24;
-        if (from % 8) + len > 8 {
-            num |= (self.0[from_chunk + 1] as u32) << 16;
-        }
-        if (from % 8) + len > 16 {
-            num |= (self.0[from_chunk + 2] as u32) << 8;
-        }
-
-        // 012345678012345678
-        //   2----------12
-        num >>= 32 - len - (from % 8);
-        num &= (1 << len) - 1;
-        num as u16
+    pub fn bits(&self, from: usize, len: usize) -> u64 {
+        self.0
+            .read_at(from, len)
+            .unwrap_or_else(|e| panic!("truncated packet: {}", e))
     }
 
     pub fn version(&self, packet_start: usize) -> u8 {
@@ -272,7 +243,7 @@ mod test {
     #[test]
     fn test_parse_and_bits() {
         let p = Packet::from_input("3806F45");
-        assert_eq!(p.0, vec![0x38, 0x06, 0xf4, 0x50]);
+        assert_eq!(p.0.to_string(), "0011100000000110111101000101");
         assert_eq!(p.bits(0, 1), 0b0);
         assert_eq!(p.bits(2, 1), 0b1);
         assert_eq!(p.bits(0, 6), 0b001110);
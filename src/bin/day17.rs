@@ -1,78 +1,45 @@
 use std::cmp::max;
 
+use aoc21::{aabb::Aabb, input::AoCLineInput, pos::V2};
+
 aoc21::main!(17);
 
+type Target = Aabb<i32, 2>;
 type Input = Target;
 type Output = i32;
 
 fn parse(s: &str) -> Input {
-    let (xs, ys) = s.split_once(": x=").unwrap().1.split_once(", y=").unwrap();
-    let (x0, x1) = xs.split_once("..").unwrap();
-    let (y0, y1) = ys.split_once("..").unwrap();
-    Target {
-        x_from: x0.parse().unwrap(),
-        x_to: x1.parse().unwrap(),
-        y_from: y0.parse().unwrap(),
-        y_to: y1.parse().unwrap(),
-    }
-}
-
-struct Target {
-    x_from: i32,
-    x_to: i32,
-    y_from: i32,
-    y_to: i32,
-}
-
-impl Target {
-    pub fn contains(&self, pos: &V2) -> bool {
-        self.x_from <= pos.x && pos.x <= self.x_to && self.y_from <= pos.y && pos.y <= self.y_to
-    }
-}
-
-struct V2 {
-    x: i32,
-    y: i32,
-}
-
-impl V2 {
-    pub fn new(x: i32, y: i32) -> Self {
-        V2 { x, y }
-    }
+    Target::from_line(s.trim().strip_prefix("target area: ").unwrap())
 }
 
 struct Probe {
-    pos: V2,
-    vel: V2,
+    pos: V2<i32>,
+    vel: V2<i32>,
     max_y: i32,
 }
 
 impl Probe {
     pub fn new(vx: i32, vy: i32) -> Self {
         Probe {
-            pos: V2::new(0, 0),
-            vel: V2::new(vx, vy),
+            pos: V2::zero(),
+            vel: V2::new([vx, vy]),
             max_y: 0,
         }
     }
 
     pub fn step(&mut self) {
-        self.pos.x += self.vel.x;
-        self.pos.y += self.vel.y;
-        if self.pos.y > self.max_y {
-            self.max_y = self.pos.y;
-        }
-        if self.vel.x > 0 {
-            self.vel.x -= 1;
-        } else if self.vel.x < 0 {
-            self.vel.x += 1;
+        self.pos += self.vel;
+        if self.pos.y() > self.max_y {
+            self.max_y = self.pos.y();
         }
-        self.vel.y -= 1;
+        // Drag pulls x towards 0, gravity pulls y down.
+        self.vel[0] -= self.vel.x().signum();
+        self.vel[1] -= 1;
     }
 
     pub fn shoot(&mut self, target: &Target) -> Option<u32> {
         let mut time = 0;
-        while self.pos.x <= target.x_to && self.pos.y >= target.y_from {
+        while self.pos.x() <= target.max.x() && self.pos.y() >= target.min.y() {
             if target.contains(&self.pos) {
                 return Some(time);
             }
@@ -98,8 +65,8 @@ fn part1(target: &Input) -> Output {
 
 fn part2(target: &Input) -> Output {
     let mut count = 0;
-    for vx in 1..target.x_to + 1 {
-        for vy in target.y_from..target.x_to {
+    for vx in 1..target.max.x() + 1 {
+        for vy in target.min.y()..target.max.x() {
             let mut p = Probe::new(vx, vy);
             if p.shoot(target).is_some() {
                 count += 1
@@ -1,18 +1,22 @@
-use aoc21::pos::Pos;
+use aoc21::pos::{Dir4, Pos};
 
 aoc21::simple_main!(2);
 
 type Input = Vec<(String, i32)>;
 type Output = i32;
 
+fn command_dir(cmd: &str) -> Dir4 {
+    match cmd {
+        "forward" => Dir4::East,
+        "up" => Dir4::North,
+        "down" => Dir4::South,
+        _ => panic!("unknown command {}", cmd),
+    }
+}
+
 fn part1(inp: &Input) -> Output {
     inp.iter()
-        .map(|(m, n)| match m.as_str() {
-            "forward" => Pos::from_x(*n),
-            "up" => Pos::from_y(-*n),
-            "down" => Pos::from_y(*n),
-            _ => panic!("unknown"),
-        })
+        .map(|(m, n)| command_dir(m).delta().scale(*n))
         .sum::<Pos<Output>>()
         .mul()
 }
@@ -24,7 +28,7 @@ fn part2(inp: &Input) -> Output {
             "forward" => (x + n, y + n * a, a),
             "up" => (x, y, a - n),
             "down" => (x, y, a + n),
-            _ => panic!("unknown"),
+            _ => panic!("unknown command {}", m),
         });
     x * y
 }
@@ -1,127 +1,74 @@
-use iter::{ParallelBridge, ParallelExtend, ParallelIterator};
-use itertools::Itertools;
-use rayon::*;
-use std::collections::HashSet;
+use aoc21::input::{self, AoCInput};
+use aoc21::matrix::{BitGrid, Mat, MatGet, SparseGrid};
 
 aoc21::main!(20);
 
-type Input = (Vec<char>, Sparse);
+type Input = (Vec<bool>, SparseGrid<bool>);
 type Output = usize;
 
 fn parse(inp: &str) -> Input {
     let (top, bot) = inp.split_once("\n\n").unwrap();
-    let mut img = Sparse::new(false);
-    for (i, line) in bot.lines().enumerate() {
-        for (j, char) in line.char_indices() {
-            match char {
-                '#' => img.set((i as isize, j as isize), true),
-                '.' => {}
-                _ => panic!("Unexpected char"),
-            }
-        }
-    }
-
-    (top.chars().collect_vec(), img)
+    let alg = input::parse_char_line(top).unwrap();
+    (alg, SparseGrid::from_mat(&Mat::from_input(bot), false))
 }
 
 fn part1((alg, img): &Input) -> Output {
-    image_enhance(alg, img, 2).iter_idx().count()
+    image_enhance(alg, img, 2).count_ones()
 }
 
 fn part2((alg, img): &Input) -> Output {
-    image_enhance(alg, img, 50).iter_idx().count()
-}
-
-fn image_enhance(alg: &Vec<char>, img: &Sparse, n: usize) -> Sparse {
-    let mut img = image_improve(alg, img);
-    for _ in 1..n {
-        img = image_improve(alg, &img)
-    }
-    img
+    image_enhance(alg, img, 50).count_ones()
 }
 
-fn image_improve(alg: &Vec<char>, img: &Sparse) -> Sparse {
-    let mut min = (0, 0);
-    let mut max = (0, 0);
-
-    for (i, j) in img.iter_idx() {
-        min.0 = std::cmp::min(min.0, *i);
-        min.1 = std::cmp::min(min.1, *j);
-        max.0 = std::cmp::max(max.0, *i);
-        max.1 = std::cmp::max(max.1, *j);
+/// The image grows by one cell per step, so padding by `n` leaves room for
+/// all of it. Everything beyond is background.
+fn image_enhance(alg: &[bool], img: &SparseGrid<bool>, n: usize) -> BitGrid {
+    let ((li, lj), (hi, hj)) = img.bounds().unwrap();
+    let pad = n as isize;
+    let window = img.window((li - pad, lj - pad), (hi + pad, hj + pad));
+    let mut image = BitGrid::from_grid(&window, |lit| *lit);
+    let mut background = *img.background();
+    for _ in 0..n {
+        image = enhance_step(alg, &image, background);
+        // The background sees nothing but background.
+        background = alg[if background { 0x1ff } else { 0 }];
     }
-
-    Sparse::from_par_iter(
-        if img.empty {
-            alg[0x1ff] == '#'
-        } else {
-            alg[0] == '#'
-        },
-        (min.0 - 1..max.0 + 2)
-            .cartesian_product(min.1 - 1..max.1 + 2)
-            .par_bridge()
-            .map(|(i, j)| {
-                let mut kernel = 0usize;
-                for (ki, kj) in (i - 1..i + 2).cartesian_product(j - 1..j + 2) {
-                    kernel <<= 1;
-                    if img.get((ki, kj)) {
-                        kernel |= 1;
-                    }
-                }
-                ((i, j), alg[kernel] == '#')
-            }),
-    )
+    image
 }
 
-type Ix = (isize, isize);
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Sparse {
-    els: HashSet<Ix>,
-    empty: bool,
+/// Splits the 3x3 kernel into nine bit planes, top left first, so that
+/// plane `k` holds bit `8 - k` of every cell's index into `alg`.
+fn enhance_step(alg: &[bool], image: &BitGrid, background: bool) -> BitGrid {
+    let planes: Vec<BitGrid> = (-1..=1)
+        .flat_map(|di| (-1..=1).map(move |dj| (di, dj)))
+        .map(|(di, dj)| {
+            // Shifts fill with zeros, so shift the inverse of a lit background.
+            let shift = |g: &BitGrid| g.shift_rows(-di, false).shift_cols(-dj, false);
+            if background {
+                !&shift(&!image)
+            } else {
+                shift(image)
+            }
+        })
+        .collect();
+    let zeros = BitGrid::new(image.height(), image.width());
+    lookup(&zeros, &planes, alg)
 }
 
-impl Sparse {
-    fn new(empty: bool) -> Self {
-        Self {
-            els: HashSet::new(),
-            empty,
-        }
+/// `table[index]` for every cell at once, where `planes[0]` holds the most
+/// significant bit of the index. Evaluated as a tree of multiplexers that
+/// skips the halves of `table` which do not depend on the remaining planes.
+fn lookup(zeros: &BitGrid, planes: &[BitGrid], table: &[bool]) -> BitGrid {
+    if table.iter().all(|&t| t == table[0]) {
+        return if table[0] { !zeros } else { zeros.clone() };
     }
-
-    fn from_par_iter(empty: bool, iter: impl ParallelIterator<Item = (Ix, bool)>) -> Self {
-        let mut els = HashSet::new();
-        els.par_extend(iter.filter_map(|(ix, c)| if c != empty { Some(ix) } else { None }));
-        Self { els, empty }
-    }
-
-    fn get(&self, ix: Ix) -> bool {
-        self.els.contains(&ix) ^ self.empty
-    }
-
-    fn set(&mut self, ix: Ix, el: bool) {
-        if el == self.empty {
-            self.els.remove(&ix);
-        } else {
-            self.els.insert(ix);
-        }
-    }
-
-    #[allow(dead_code)]
-    fn show(&self, (li, lj): Ix, (hi, hj): Ix) -> Vec<String> {
-        let mut lines = Vec::new();
-        for i in li..hi + 1 {
-            let mut line = String::new();
-            for j in lj..hj + 1 {
-                line += if self.get((i, j)) { "#" } else { "." };
-            }
-            lines.push(line);
-        }
-        lines
-    }
-
-    fn iter_idx(&self) -> impl Iterator<Item = &Ix> {
-        self.els.iter()
+    let (plane, rest) = planes.split_first().unwrap();
+    let (lo, hi) = table.split_at(table.len() / 2);
+    if lo == hi {
+        return lookup(zeros, rest, lo);
     }
+    let (lo, hi) = (lookup(zeros, rest, lo), lookup(zeros, rest, hi));
+    &lo ^ &(&(&lo ^ &hi) & plane)
 }
 
 aoc21::test_part1!(TEST_INPUT, 35);
@@ -162,11 +109,38 @@ fn test_enhance1() {
 ...............
 ...............";
 
-    let improve1 = image_improve(&alg, &img);
-    let improve2 = image_improve(&alg, &improve1);
+    // Padded by `n`, so the original origin ends up at `(n, n)`.
+    let improve1 = SparseGrid::from_mat(&image_enhance(&alg, &img, 1).to_mat(), false);
+    let improve2 = SparseGrid::from_mat(&image_enhance(&alg, &img, 2).to_mat(), false);
+
+    assert_eq!(
+        improve1
+            .window((-4, -4), (10, 10))
+            .render()
+            .to_string()
+            .trim_end(),
+        want1
+    );
+    assert_eq!(
+        improve2
+            .window((-3, -3), (11, 11))
+            .render()
+            .to_string()
+            .trim_end(),
+        want2
+    );
+}
 
-    assert_eq!(improve1.show((-5, -5), (9, 9)).join("\n"), want1);
-    assert_eq!(improve2.show((-5, -5), (9, 9)).join("\n"), want2);
+#[test]
+fn test_enhance_step_matches_kernel() {
+    let (alg, img) = parse(TEST_INPUT);
+    let image = BitGrid::from_grid(&img.to_mat(), |lit| *lit);
+    for background in [false, true] {
+        let want = BitGrid::from_fn(image.height(), image.width(), |(i, j)| {
+            alg[image.window9((i as isize, j as isize), background)]
+        });
+        assert_eq!(enhance_step(&alg, &image, background), want);
+    }
 }
 
 #[allow(dead_code)]
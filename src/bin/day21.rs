@@ -1,5 +1,4 @@
-use std::collections::BTreeMap;
-
+use aoc21::evolve::Evolution;
 use aoc21::input::AoCInput;
 use itertools::Itertools;
 
@@ -9,7 +8,7 @@ type N = u64;
 type Input = Game;
 type Output = N;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct Game {
     scores: [N; 2],
     pos: [N; 2],
@@ -33,28 +32,6 @@ impl AoCInput for Game {
     }
 }
 
-// Ensure Games are ordered according to their minimal score.
-// This will make sure we never process an end state before one before.
-impl PartialOrd for Game {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match std::cmp::min(self.scores[0], self.scores[1])
-            .partial_cmp(std::cmp::min(&other.scores[0], &other.scores[1]))
-        {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        match self.roll_count.partial_cmp(&other.roll_count) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        match self.player_turn.partial_cmp(&other.player_turn) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        self.pos.partial_cmp(&other.pos)
-    }
-}
-
 const DIRAC_ROLLS: [(N, N); 7] = [(1, 3), (3, 4), (6, 5), (7, 6), (6, 7), (3, 8), (1, 9)];
 
 impl Game {
@@ -102,24 +79,20 @@ fn part1(game: &Input) -> Output {
 }
 
 fn part2(game: &Input) -> Output {
-    // PartialOrd of Game ensures that lowest scores are first.
-    let mut games = BTreeMap::<Game, N>::new();
-    games.insert(game.clone(), 1);
-    let mut player_1_wins = 0;
-    let mut player_2_wins = 0;
-    while let Some((game, n)) = games.pop_first() {
-        for (g, dn) in game.dirac_roll() {
-            let game_n = n * dn;
-            if g.scores[0] >= 21 {
-                player_1_wins += game_n;
-            } else if g.scores[1] >= 21 {
-                player_2_wins += game_n;
-            } else {
-                *games.entry(g).or_default() += game_n;
-            }
-        }
-    }
-    std::cmp::max(player_1_wins, player_2_wins)
+    let mut games = Evolution::new([*game].into_iter().collect());
+    // Every turn scores at least one point, so no game lasts 42 turns.
+    let over = |g: &Game| g.scores.iter().any(|&s| s >= 21);
+    let done = games.run_until_absorbed(42, Game::dirac_roll, over);
+    assert!(done, "games left after 42 turns");
+    let wins = |player: usize| {
+        games
+            .absorbed()
+            .iter()
+            .filter(|(g, _)| g.scores[player] >= 21)
+            .map(|(_, n)| n)
+            .sum::<N>()
+    };
+    std::cmp::max(wins(0), wins(1))
 }
 
 aoc21::test_part1!(TEST_INPUT, 739785);
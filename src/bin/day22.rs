@@ -1,104 +1,66 @@
+use aoc21::aabb::Aabb;
+use aoc21::counter::Counter;
 use aoc21::input::AoCLineInput;
-use itertools::Itertools as _;
-use rayon::iter::{ParallelBridge, ParallelIterator as _};
-use std::collections::HashSet;
 
 aoc21::simple_main!(22);
 
 type Input = Vec<Cube>;
 type N = i64;
 type Output = N;
+type Cuboid = Aabb<N, 3>;
 
 fn part1(cubes: &Input) -> Output {
-    let in50 = |n| -50 <= n && n <= 50;
-    part2(
-        &cubes
-            .iter()
-            .filter(|c| {
-                in50(c.x.0)
-                    && in50(c.x.1)
-                    && in50(c.y.0)
-                    && in50(c.y.1)
-                    && in50(c.z.0)
-                    && in50(c.z.1)
-            })
-            .cloned()
-            .collect_vec(),
-    )
+    lit_volume(cubes, &Aabb::from_ranges([(-50, 50); 3]))
 }
 
 fn part2(cubes: &Input) -> Output {
-    let mut xs = HashSet::new();
-    let mut ys = HashSet::new();
-    let mut zs = HashSet::new();
+    let everything = cubes
+        .iter()
+        .map(|c| c.cuboid)
+        .reduce(|a, b| a.union_bounds(&b))
+        .unwrap();
+    lit_volume(cubes, &everything)
+}
+
+/// Lit volume inside `region` after running all steps.
+///
+/// Keeps a multiset of signed cuboids whose weighted volumes sum up to the
+/// lit volume (inclusion–exclusion). Every step cancels its overlap with all
+/// cuboids so far, and on-steps add themselves on top.
+fn lit_volume(cubes: &[Cube], region: &Cuboid) -> N {
+    let mut signed: Counter<Cuboid, N> = Counter::new();
     for cube in cubes {
-        xs.insert(cube.x.0);
-        xs.insert(cube.x.1 + 1);
-        ys.insert(cube.y.0);
-        ys.insert(cube.y.1 + 1);
-        zs.insert(cube.z.0);
-        zs.insert(cube.z.1 + 1);
-    }
-    let xs = xs.into_iter().sorted().collect_vec();
-    let ys = ys.into_iter().sorted().collect_vec();
-    let zs = zs.into_iter().sorted().collect_vec();
+        let Some(cuboid) = cube.cuboid.intersection(region) else {
+            continue;
+        };
 
-    xs.iter()
-        .zip(xs.iter().skip(1))
-        .par_bridge()
-        .map(|(x, x_to)| {
-            let mut on = 0;
-            for (y, y_to) in ys.iter().zip(ys.iter().skip(1)) {
-                for (z, z_to) in zs.iter().zip(zs.iter().skip(1)) {
-                    for cube in cubes.iter().rev() {
-                        if cube.contains((*x, *y, *z)) {
-                            if cube.on {
-                                on += (x_to - x) * (y_to - y) * (z_to - z);
-                            }
-                            break;
-                        }
-                    }
-                }
+        let mut update = Counter::new();
+        for (other, sign) in signed.iter() {
+            if let Some(overlap) = other.intersection(&cuboid) {
+                update.add(overlap, -sign);
             }
-            on
-        })
-        .sum()
+        }
+        if cube.on {
+            update.inc(cuboid);
+        }
+        signed += &update;
+    }
+    signed.iter().map(|(c, sign)| c.volume() * sign).sum()
 }
 
 #[derive(Debug, Copy, Clone)]
 struct Cube {
     on: bool,
-    x: (N, N),
-    y: (N, N),
-    z: (N, N),
-}
-
-impl Cube {
-    pub fn new(on: bool, x: (N, N), y: (N, N), z: (N, N)) -> Self {
-        Self { on, x, y, z }
-    }
-
-    pub fn contains(&self, (x, y, z): (N, N, N)) -> bool {
-        self.x.0 <= x
-            && x <= self.x.1
-            && self.y.0 <= y
-            && y <= self.y.1
-            && self.z.0 <= z
-            && z <= self.z.1
-    }
+    cuboid: Aabb<N, 3>,
 }
 
 impl AoCLineInput for Cube {
     fn from_line(s: &str) -> Self {
         let (state, coords) = s.split_once(" ").unwrap();
-        let xyz = coords
-            .split(",")
-            .map(|c| {
-                let (lo, hi) = c.split_once("=").unwrap().1.split_once("..").unwrap();
-                (lo.parse().unwrap(), hi.parse().unwrap())
-            })
-            .collect_vec();
-        Cube::new(state == "on", xyz[0], xyz[1], xyz[2])
+        Cube {
+            on: state == "on",
+            cuboid: Aabb::from_line(coords),
+        }
     }
 }
 
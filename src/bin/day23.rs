@@ -1,9 +1,9 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{HashMap, HashSet};
 
 use aoc21::input::AoCInput;
+use aoc21::search::{self, BucketQueue, HashIndex};
 use itertools::Itertools;
 use lazy_static::lazy_static;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 aoc21::simple_main!(23);
 
@@ -12,7 +12,7 @@ type Output = u32;
 
 fn part1(s: &Input) -> Output {
     let state = State::from_input(s);
-    find_shortest(&state).unwrap().cost
+    find_shortest(&state).unwrap()
 }
 
 
@@ -23,37 +23,24 @@ fn part2(s: &Input) -> Output {
 
     let state = State::from_input(&extended_input.join("\n"));
 
-    find_shortest(&state).unwrap().cost
+    find_shortest(&state).unwrap()
 }
 
-fn find_shortest(state: &State) -> Option<State> {
-    let mut states = BinaryHeap::new();
-    let mut seen = HashSet::new();
-    states.push(state.clone());
-    seen.insert(state.clone());
-    while let Some(state) = states.pop() {
-        if state.is_final() {
-            return Some(state);
-        }
-        let moves = state.moves();
-        for new_state in moves
-            .par_iter()
-            .filter_map(|m| {
-                let mut new_state = state.clone();
-                new_state.make_move(*m);
-                if seen.contains(&new_state) {
-                    None
-                } else {
-                    Some(new_state)
-                }
+fn find_shortest(state: &State) -> Option<Energy> {
+    let found = search::dijkstra_with(
+        BucketQueue::default(),
+        HashIndex::default(),
+        *state,
+        |&state| {
+            state.moves().into_iter().map(move |m| {
+                let mut next = state;
+                next.make_move(m);
+                (next, m.3)
             })
-            .collect::<Vec<State>>()
-        {
-            seen.insert(new_state.clone());
-            states.push(new_state);
-        }
-    }
-    None
+        },
+        State::is_final,
+    );
+    found.map(|found| found.cost)
 }
 
 type Pod = u8;
@@ -108,7 +95,7 @@ lazy_static! {
     };
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 struct State {
     // We have 7 hallway positions and 16 rooms = 23 tiles.
     // Each of them requires 3 bits to encode Pod.
@@ -117,13 +104,12 @@ struct State {
     // which results in 117 bits.
     // Thus, a state can be represented with u128
     state: u128,
-    cost: u32,
     depth: u8,
 }
 
 impl State {
     fn new(flat_pods: &[char]) -> Self {
-        let mut s = Self { state: 0, cost: 0, depth: 2 };
+        let mut s = Self { state: 0, depth: 2 };
         if flat_pods.len() > 8 {
             s.depth = 4;
         }
@@ -344,8 +330,7 @@ impl State {
         moves
     }
 
-    fn make_move(&mut self, (frm, to, pod, cost): (Pos, Pos, Pod, Energy)) {
-        self.cost += cost;
+    fn make_move(&mut self, (frm, to, pod, _): (Pos, Pos, Pod, Energy)) {
         self.remove(frm);
         self.put(to, pod);
     }
@@ -420,28 +405,6 @@ impl std::fmt::Display for State {
     }
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.cost.partial_cmp(&other.cost) {
-            Some(core::cmp::Ordering::Equal) => {}
-            Some(ord) => return Some(ord.reverse()),
-            None => return None,
-        }
-        self.state.partial_cmp(&other.state)
-    }
-}
-
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.cost.cmp(&other.cost) {
-            std::cmp::Ordering::Equal => {}
-            ord => return ord.reverse(),
-        }
-
-        self.state.cmp(&other.state)
-    }
-}
-
 impl AoCInput for State {
     fn from_input(s: &str) -> Self {
         let lines = s.lines().skip(2);
@@ -1,48 +1,44 @@
-use aoc21::matrix::{MatGet as _, MatMut as _, Torus};
+use aoc21::matrix::{BitGrid, Torus};
 
 aoc21::simple_main!(25);
 
-type Input = Torus<char>;
-type Output = usize;
-
-fn part1(mat: &Input) -> Output {
-    let mut tor = mat.clone();
-
-    let mut moved = true;
-    let mut steps = 0;
+aoc21::char_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Cucumber {
+        East = '>',
+        South = 'v',
+        Empty = '.',
+    }
+}
 
-    while moved {
-        let prev_tor = tor.clone();
+use Cucumber::{East, South};
 
-        moved = false;
-        steps += 1;
-        // East
-        for i in 0..tor.m {
-            for j in 0..tor.n {
-                let o = tor.east((i, j));
-                if prev_tor.get((i, j)) == '>' && prev_tor.get(o) == '.' {
-                    moved = true;
-                    *tor.get_mut((i, j)) = '.';
-                    *tor.get_mut(o) = '>';
-                }
-            }
-        }
+type Input = Torus<Cucumber>;
+type Output = usize;
 
-        let prev_tor = tor.clone();
-        // South
-        for i in 0..tor.m {
-            for j in 0..tor.n {
-                let o = tor.south((i, j));
-                if prev_tor.get((i, j)) == 'v' && prev_tor.get(o) == '.' {
-                    moved = true;
-                    *tor.get_mut((i, j)) = '.';
-                    *tor.get_mut(o) = 'v';
-                }
-            }
-        }
-    }
+/// Moves every cucumber of `herd` whose next cell is free by `shift`.
+/// Returns how many moved.
+fn move_herd(
+    herd: &mut BitGrid,
+    other: &BitGrid,
+    shift: impl Fn(&BitGrid, isize) -> BitGrid,
+) -> usize {
+    let free = !&(&*herd | other);
+    let movers = &*herd & &shift(&free, -1);
+    *herd = &(&*herd ^ &movers) | &shift(&movers, 1);
+    movers.count_ones()
+}
 
-    steps
+fn part1(tor: &Input) -> Output {
+    let mut east = BitGrid::from_grid(tor, |c| *c == East);
+    let mut south = BitGrid::from_grid(tor, |c| *c == South);
+    (1..)
+        .find(|_| {
+            let moved = move_herd(&mut east, &south, |g, d| g.shift_cols(d, true))
+                + move_herd(&mut south, &east, |g, d| g.shift_rows(d, true));
+            moved == 0
+        })
+        .unwrap()
 }
 
 fn part2(_mat: &Input) -> Output {
@@ -1,34 +1,32 @@
+use aoc21::bits::BitVec;
+use aoc21::counter::Counter;
+use aoc21::input::Bits;
 use itertools::Itertools;
 
 aoc21::simple_main!(3);
 
-type Input = Vec<Vec<u32>>;
+type Input = Vec<Bits<BitVec>>;
 type Output = u32;
 
 fn part1(inp: &Input) -> Output {
     let n = inp[0].len();
-    let gamma = bits_to_num(&most_common(inp));
+    let gamma = most_common(inp).to_u64() as u32;
     gamma * (gamma ^ ((1 << n) - 1))
 }
 
-fn bits_to_num(bits: &Vec<u32>) -> u32 {
-    bits.into_iter().fold(0, |acc, b| (acc << 1) + b)
+fn most_common(inp: &Input) -> BitVec {
+    let n = inp.len() as u64;
+    let ones = ones_per_column(inp);
+    (0..inp[0].len())
+        .map(|col| ones.frequency(&col) > n / 2)
+        .collect()
 }
 
-fn most_common(inp: &Input) -> Vec<u32> {
-    let n = inp.len() as u32;
-    elementwise_sum(inp)
-        .iter()
-        .map(|sum| if *sum > n / 2 { 1 } else { 0 })
-        .collect_vec()
-}
-
-fn elementwise_sum(inp: &Input) -> Vec<u32> {
-    (0..inp[0].len()).map(|row| sum_row(inp, row)).collect_vec()
-}
-
-fn sum_row(inp: &Input, row: usize) -> u32 {
-    inp.into_iter().map(|v| v[row]).sum()
+/// How many of the numbers have a one in each column.
+fn ones_per_column(inp: &Input) -> Counter<usize> {
+    inp.iter()
+        .flat_map(|v| (0..v.len()).filter(|&col| v[col]))
+        .collect()
 }
 
 fn part2(inp: &Input) -> Output {
@@ -41,18 +39,14 @@ fn rating(inp: &Input, invert: bool) -> u32 {
     let mut candidates = inp.clone();
     let n = candidates[0].len();
     for i in 0..n {
-        let sum = sum_row(&candidates, i);
-        let keep = if (2 * sum >= candidates.len() as u32) ^ invert {
-            1
-        } else {
-            0
-        };
+        let ones = candidates.iter().filter(|c| c[i]).count();
+        let keep = (2 * ones >= candidates.len()) ^ invert;
         candidates = candidates
             .into_iter()
             .filter(|c| c[i] == keep)
             .collect_vec();
         if candidates.len() == 1 {
-            return bits_to_num(&candidates[0]);
+            return candidates[0].to_u64() as u32;
         }
     }
     panic!("No candidate found")
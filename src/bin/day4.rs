@@ -2,8 +2,9 @@ use std::collections::{HashMap, HashSet};
 
 use aoc21::{
     input::{self, AoCInput},
-    matrix::{Ix, Mat, MatGet},
+    matrix::{Mat, MatGet},
 };
+use itertools::Itertools;
 
 fn main() {
     aoc21::solve("day4", parse, part1, part2);
@@ -23,7 +24,7 @@ fn part1(inp @ (_, boards): &Input) -> Output {
     let (win_draw, win_k) = winners[0];
     let mut sum = 0;
     for (ij, v) in boards[win_k].iter() {
-        if !marked.contains(&(win_k, ij)) {
+        if !marked[win_k][ij] {
             sum += v;
         }
     }
@@ -31,12 +32,9 @@ fn part1(inp @ (_, boards): &Input) -> Output {
     sum * win_draw
 }
 
-fn run_winners(
-    (draw, boards): &Input,
-    find_first: bool,
-) -> (Vec<(u32, usize)>, HashSet<(usize, Ix)>) {
+fn run_winners((draw, boards): &Input, find_first: bool) -> (Vec<(u32, usize)>, Vec<Mat<bool>>) {
     let mut num_to_pos = HashMap::new();
-    let mut marked = HashSet::new();
+    let mut marked = boards.iter().map(|b| b.map(|_| false)).collect_vec();
     for (k, b) in boards.iter().enumerate() {
         b.iter().for_each(|(ij, n)| {
             num_to_pos.insert((k, n), ij);
@@ -47,14 +45,12 @@ fn run_winners(
     let mut winners = Vec::new();
     'drawLoop: for d in draw {
         for k in playing.clone().into_iter() {
-            let b = &boards[k];
-            if let Some((i, j)) = num_to_pos.get(&(k, *d)) {
-                marked.insert((k, (*i, *j)));
+            if let Some(&(i, j)) = num_to_pos.get(&(k, *d)) {
+                let b = &mut marked[k];
+                b[(i, j)] = true;
 
                 // Check rows and columns for bingo
-                if (0..b.m).all(|i| marked.contains(&(k, (i, *j))))
-                    || (0..b.n).all(|j| marked.contains(&(k, (*i, j))))
-                {
+                if b.row(i).iter().all(|m| *m) || b.col(j).all(|m| *m) {
                     winners.push((*d, k));
                     playing.remove(&k);
                     if find_first {
@@ -72,7 +68,7 @@ fn part2(inp @ (_, boards): &Input) -> Output {
     let (win_draw, win_k) = winners[winners.len() - 1];
     let mut sum = 0;
     for (ij, v) in boards[win_k].iter() {
-        if !marked.contains(&(win_k, ij)) {
+        if !marked[win_k][ij] {
             sum += v;
         }
     }
@@ -1,50 +1,23 @@
-use std::collections::HashMap;
-
-use aoc21::input::AoCLineInput;
-use aoc21::pos::Pos;
+use aoc21::segment::{count_overlaps, Segment};
 use itertools::Itertools;
 
-fn main() {
-    aoc21::solve("day5", parse, part1, part2);
-}
+aoc21::simple_main!(5);
 
-type N = i16;
-type Input = Vec<(Pos<N>, Pos<N>)>;
+type N = i64;
+type Input = Vec<Segment<N>>;
 type Output = usize;
 
-fn parse(s: &str) -> Input {
-    s.lines()
-        .map(|line| {
-            let (from, to) = line.split_once(" -> ").unwrap();
-            (<Pos<N>>::from_line(from), Pos::from_line(to))
-        })
-        .collect_vec()
-}
-
 fn part1(inp: &Input) -> Output {
-    part2(
-        &inp.clone()
-            .into_iter()
-            .filter(|(a, b)| a.x == b.x || a.y == b.y)
+    count_overlaps(
+        &inp.iter()
+            .filter(|s| s.is_axis_aligned())
+            .cloned()
             .collect_vec(),
     )
 }
 
 fn part2(inp: &Input) -> Output {
-    let mut pixels: HashMap<Pos<N>, u8> = HashMap::new();
-    inp.into_iter().for_each(|(from, to)| {
-        let diff = *to - *from;
-        let diff_len = std::cmp::max(diff.x.abs(), diff.y.abs());
-        let step = diff.elementwise_clamp(-1, 1);
-
-        let mut curr = from.clone();
-        for _ in 0..diff_len + 1 {
-            *pixels.entry(curr).or_default() += 1;
-            curr += step;
-        }
-    });
-
-    pixels.into_values().filter(|x| *x >= 2).count()
+    count_overlaps(inp)
 }
 
 #[allow(dead_code)]
@@ -1,6 +1,5 @@
+use aoc21::evolve::Evolution;
 use aoc21::input::CSVLine;
-use memoize::memoize;
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 aoc21::simple_main!(6);
 
@@ -8,22 +7,20 @@ type Input = CSVLine<u8>;
 type Output = u64;
 
 fn part1(inp: &Input) -> Output {
-    inp.par_iter().map(|f| fish(*f, 80)).sum()
+    school_size(inp, 80)
 }
 
-#[memoize]
-fn fish(state: u8, time: u16) -> Output {
-    if time == 0 {
-        1
-    } else if state == 0 {
-        fish(8, time - 1) + fish(6, time - 1)
-    } else {
-        fish(state - 1, time - 1)
-    }
+fn part2(inp: &Input) -> Output {
+    school_size(inp, 256)
 }
 
-fn part2(inp: &Input) -> Output {
-    inp.par_iter().map(|f| fish(*f, 256)).sum()
+fn school_size(timers: &[u8], days: usize) -> Output {
+    let mut school = Evolution::new(timers.iter().copied().collect());
+    school.run(days, |&timer| match timer {
+        0 => vec![(6, 1), (8, 1)],
+        t => vec![(t - 1, 1)],
+    });
+    school.live().total()
 }
 
 #[allow(dead_code)]
@@ -1,11 +1,12 @@
-use std::collections::HashSet;
-
+use aoc21::automaton::Neighbourhood;
+use aoc21::flood::label_components;
+use aoc21::input::Digits;
 use aoc21::matrix::{Ix, Mat, MatGet};
 use itertools::Itertools;
 
 aoc21::simple_main!(9);
 
-type Input = Mat<u8>;
+type Input = Digits<Mat<u8>>;
 type Output = u32;
 
 fn part1(inp: &Input) -> Output {
@@ -17,25 +18,12 @@ fn low_points<'a>(mat: &'a Input) -> impl Iterator<Item = (Ix, u8)> + 'a {
         .filter(|(pos, val)| *val < mat.iter_bordering_el(*pos).min().unwrap())
 }
 
+/// Every location except those of height 9 is part of exactly one basin.
 fn part2(inp: &Input) -> Output {
-    low_points(inp)
-        .map(|(low_pos, low_height)| {
-            let mut stack = vec![(low_pos, low_height)];
-            let mut seen = HashSet::from([low_pos]);
-            let mut size = 0;
-            while let Some((pos, height)) = stack.pop() {
-                size += 1;
-
-                for (np, nh) in inp.iter_bordering(pos) {
-                    if nh == 9 || nh <= height || seen.contains(&np) {
-                        continue;
-                    }
-                    seen.insert(np);
-                    stack.push((np, nh));
-                }
-            }
-            size
-        })
+    label_components(&inp.0, Neighbourhood::Four, |h| h != 9)
+        .components
+        .iter()
+        .map(|basin| basin.size as u32)
         .sorted()
         .rev()
         .take(3)
@@ -0,0 +1,290 @@
+// Packed bit vectors and a cursor to read them as big-endian numbers.
+use std::{fmt::Display, ops::Index};
+
+use crate::input::{AoCInput, AoCLineInput, Bits, CharError, HexDigits};
+
+/// Bits stored most significant first, 64 per word.
+/// Unused bits of the last word are always zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncated {
+    pub position: usize,
+    pub requested: usize,
+    pub available: usize,
+}
+
+impl Display for Truncated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "requested {} bits at position {} but only {} are left",
+            self.requested, self.position, self.available
+        )
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+impl BitVec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        if bit {
+            *self.words.last_mut().unwrap() |= 1 << (63 - self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    /// Append the lowest `n` bits of `value`, most significant first.
+    pub fn push_bits(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "cannot push more than 64 bits at once");
+        for i in (0..n).rev() {
+            self.push((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i < self.len {
+            Some((self.words[i / 64] >> (63 - i % 64)) & 1 == 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        assert!(
+            i < self.len,
+            "bit {} out of range for length {}",
+            i,
+            self.len
+        );
+        let mask = 1 << (63 - i % 64);
+        if bit {
+            self.words[i / 64] |= mask;
+        } else {
+            self.words[i / 64] &= !mask;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i).unwrap())
+    }
+
+    /// Read `n <= 64` bits starting at `pos` as a big-endian number.
+    pub fn read_at(&self, pos: usize, n: usize) -> Result<u64, Truncated> {
+        assert!(n <= 64, "cannot read more than 64 bits at once");
+        if pos + n > self.len {
+            return Err(Truncated {
+                position: pos,
+                requested: n,
+                available: self.len.saturating_sub(pos),
+            });
+        }
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let (w, off) = (pos / 64, pos % 64);
+        let mut num = self.words[w] << off;
+        if off + n > 64 {
+            num |= self.words[w + 1] >> (64 - off);
+        }
+        Ok(num >> (64 - n))
+    }
+
+    /// The whole vector as a number. Panics if it is longer than 64 bits.
+    pub fn to_u64(&self) -> u64 {
+        self.read_at(0, self.len).unwrap()
+    }
+
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader { bits: self, pos: 0 }
+    }
+
+    pub fn from_binary(s: &str) -> Result<Self, CharError> {
+        Bits::<BitVec>::try_from_line(s).map(|b| b.0)
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, CharError> {
+        HexDigits::<BitVec>::try_from_line(s).map(|h| h.0)
+    }
+}
+
+impl Index<usize> for BitVec {
+    type Output = bool;
+
+    fn index(&self, i: usize) -> &bool {
+        match self.get(i) {
+            Some(true) => &true,
+            Some(false) => &false,
+            None => panic!("bit {} out of range for length {}", i, self.len),
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = Self::new();
+        bits.extend(iter);
+        bits
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|b| self.push(b));
+    }
+}
+
+impl Display for BitVec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for b in self.iter() {
+            write!(f, "{}", if b { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+impl Bits<BitVec> {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        Bits::<Vec<bool>>::try_from_line(s).map(|b| Bits(b.0.into_iter().collect()))
+    }
+}
+
+impl HexDigits<BitVec> {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        let digits = HexDigits::<Vec<u8>>::try_from_line(s)?;
+        let mut bits = BitVec::new();
+        digits.iter().for_each(|d| bits.push_bits(*d as u64, 4));
+        Ok(HexDigits(bits))
+    }
+}
+
+impl AoCLineInput for Bits<BitVec> {
+    fn from_line(s: &str) -> Self {
+        Self::try_from_line(s).unwrap_or_else(|e| panic!("{} in line {:?}", e, s))
+    }
+}
+
+impl AoCLineInput for HexDigits<BitVec> {
+    fn from_line(s: &str) -> Self {
+        Self::try_from_line(s).unwrap_or_else(|e| panic!("{} in line {:?}", e, s))
+    }
+}
+
+impl AoCInput for HexDigits<BitVec> {
+    fn from_input(s: &str) -> Self {
+        Self::from_line(s.trim_end())
+    }
+}
+
+/// Cursor over a `BitVec` that consumes bits from the front.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bits: &'a BitVec,
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bits: &'a BitVec) -> Self {
+        bits.reader()
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bits.len() - self.pos
+    }
+
+    pub fn peek(&self, n: usize) -> Result<u64, Truncated> {
+        self.bits.read_at(self.pos, n)
+    }
+
+    pub fn read(&mut self, n: usize) -> Result<u64, Truncated> {
+        let num = self.peek(n)?;
+        self.pos += n;
+        Ok(num)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Truncated> {
+        self.read(1).map(|b| b == 1)
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result<(), Truncated> {
+        if n > self.remaining() {
+            return Err(Truncated {
+                position: self.pos,
+                requested: n,
+                available: self.remaining(),
+            });
+        }
+        self.pos += n;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BitVec, Truncated};
+
+    #[test]
+    fn test_read_across_words() {
+        let hex = "0123456789ABCDEF0123456789ABCDEF";
+        let bits = BitVec::from_hex(hex).unwrap();
+        assert_eq!(bits.len(), 128);
+        assert_eq!(bits.read_at(4, 64).unwrap(), 0x123456789ABCDEF0);
+        assert_eq!(bits.read_at(60, 8).unwrap(), 0xF0);
+        assert_eq!(bits.read_at(128, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reader() {
+        let bits = BitVec::from_binary("110100101111111000101000").unwrap();
+        let mut r = bits.reader();
+        assert_eq!(r.read(3).unwrap(), 6);
+        assert_eq!(r.peek(3).unwrap(), 4);
+        assert_eq!(r.read(3).unwrap(), 4);
+        assert_eq!(r.position(), 6);
+        assert_eq!(r.remaining(), 18);
+        assert_eq!(
+            r.read(19),
+            Err(Truncated {
+                position: 6,
+                requested: 19,
+                available: 18
+            })
+        );
+        assert_eq!(r.position(), 6);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(BitVec::from_binary("0102").is_err());
+        assert!(BitVec::from_hex("0G").is_err());
+        assert_eq!(BitVec::from_binary("0101").unwrap().to_string(), "0101");
+    }
+}
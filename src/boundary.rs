@@ -0,0 +1,132 @@
+// What a grid has beyond its edges. Grids resolve every index that may lie
+// outside, from neighbour steps and signed lookups, through their policy.
+use crate::matrix::{Ix, SignedIx};
+
+pub trait Boundary<T> {
+    /// The cell standing in for `ix` in a `height x width` grid, if any.
+    fn resolve(&self, ix: SignedIx, height: usize, width: usize) -> Option<Ix>;
+
+    /// Value of indices that do not resolve to a cell.
+    fn outside(&self) -> Option<T> {
+        None
+    }
+}
+
+#[inline]
+fn inside(k: isize, len: usize) -> Option<usize> {
+    usize::try_from(k).ok().filter(|&k| k < len)
+}
+
+/// Nothing beyond the edges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bounded;
+
+impl<T> Boundary<T> for Bounded {
+    fn resolve(&self, (i, j): SignedIx, height: usize, width: usize) -> Option<Ix> {
+        Some((inside(i, height)?, inside(j, width)?))
+    }
+}
+
+/// Leaving one edge enters at the opposite one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Wrapping;
+
+impl Wrapping {
+    #[inline]
+    fn wrap(k: isize, len: usize) -> usize {
+        let len = len as isize;
+        // Neighbours are at most one period away, which needs no division.
+        if (0..len).contains(&k) {
+            k as usize
+        } else if (-len..0).contains(&k) {
+            (k + len) as usize
+        } else if (len..2 * len).contains(&k) {
+            (k - len) as usize
+        } else {
+            k.rem_euclid(len) as usize
+        }
+    }
+}
+
+impl<T> Boundary<T> for Wrapping {
+    fn resolve(&self, (i, j): SignedIx, height: usize, width: usize) -> Option<Ix> {
+        if height == 0 || width == 0 {
+            return None;
+        }
+        Some((Self::wrap(i, height), Self::wrap(j, width)))
+    }
+}
+
+/// Outside cells repeat the nearest edge cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Clamped;
+
+impl<T> Boundary<T> for Clamped {
+    fn resolve(&self, (i, j): SignedIx, height: usize, width: usize) -> Option<Ix> {
+        if height == 0 || width == 0 {
+            return None;
+        }
+        let clamp = |k: isize, len: usize| k.clamp(0, len as isize - 1) as usize;
+        Some((clamp(i, height), clamp(j, width)))
+    }
+}
+
+/// The grid is mirrored at every edge, edge cells included, so `-1` maps
+/// to `0` and `len` to `len - 1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Reflecting;
+
+impl<T> Boundary<T> for Reflecting {
+    fn resolve(&self, (i, j): SignedIx, height: usize, width: usize) -> Option<Ix> {
+        if height == 0 || width == 0 {
+            return None;
+        }
+        let reflect = |k: isize, len: usize| {
+            let k = k.rem_euclid(2 * len as isize) as usize;
+            if k < len {
+                k
+            } else {
+                2 * len - 1 - k
+            }
+        };
+        Some((reflect(i, height), reflect(j, width)))
+    }
+}
+
+/// Every cell outside holds the same value. Neighbour steps still stop at the
+/// edges, but signed lookups beyond them see the value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Constant<T>(pub T);
+
+impl<T: Copy> Boundary<T> for Constant<T> {
+    fn resolve(&self, ix: SignedIx, height: usize, width: usize) -> Option<Ix> {
+        Boundary::<T>::resolve(&Bounded, ix, height, width)
+    }
+
+    fn outside(&self) -> Option<T> {
+        Some(self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Boundary, Bounded, Clamped, Constant, Reflecting, Wrapping};
+
+    fn resolve(b: &impl Boundary<u8>, ix: (isize, isize)) -> Option<(usize, usize)> {
+        b.resolve(ix, 3, 4)
+    }
+
+    #[test]
+    fn test_policies() {
+        assert_eq!(resolve(&Bounded, (2, 3)), Some((2, 3)));
+        assert_eq!(resolve(&Bounded, (-1, 0)), None);
+        assert_eq!(resolve(&Wrapping, (-1, 4)), Some((2, 0)));
+        assert_eq!(resolve(&Wrapping, (7, -5)), Some((1, 3)));
+        assert_eq!(resolve(&Clamped, (-3, 9)), Some((0, 3)));
+        assert_eq!(resolve(&Reflecting, (-1, 4)), Some((0, 3)));
+        assert_eq!(resolve(&Reflecting, (-4, 5)), Some((2, 2)));
+        assert_eq!(resolve(&Constant(7), (3, 0)), None);
+        assert_eq!(Constant(7).outside(), Some(7));
+        assert_eq!(Boundary::<u8>::outside(&Bounded), None);
+    }
+}
@@ -1,53 +1,250 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
+    fmt::Display,
     hash::Hash,
-    ops::{Deref, DerefMut},
+    ops::{Add, AddAssign, Deref, Mul, MulAssign, Sub, SubAssign},
 };
 
 use itertools::Itertools;
 
+/// Integers usable as multiplicities. Signed counts can go negative, unsigned
+/// ones stop at zero when subtracted.
+pub trait Count: Copy + Default + Ord + Hash + Mul<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Addition that panics on overflow in debug builds and wraps otherwise.
+    fn add_count(self, other: Self) -> Self;
+    fn sub_count(self, other: Self) -> Self;
+}
+
+macro_rules! impl_count {
+    ( $sub:ident; $($t:ty),* ) => {
+        $(
+            impl Count for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn add_count(self, other: Self) -> Self {
+                    if cfg!(debug_assertions) {
+                        self.checked_add(other).unwrap_or_else(|| {
+                            panic!("count overflow: {} + {}", self, other)
+                        })
+                    } else {
+                        self.wrapping_add(other)
+                    }
+                }
+
+                #[inline]
+                fn sub_count(self, other: Self) -> Self {
+                    self.$sub(other)
+                }
+            }
+        )*
+    };
+}
+
+impl_count!(saturating_sub; u8, u16, u32, u64, u128, usize);
+impl_count!(sub; i8, i16, i32, i64, i128, isize);
+
+/// A multiset. Keys whose count drops to zero are removed, which is why the
+/// map is only lent out immutably.
 #[derive(Debug, Clone)]
-pub struct Counter<T>(HashMap<T, u64>);
+pub struct Counter<T, C = u64>(HashMap<T, C>);
 
-impl<T: Hash + Eq> Counter<T> {
+impl<T: Hash + Eq, C: Count> Counter<T, C> {
     pub fn new() -> Self {
         Self(HashMap::new())
     }
 
-    pub fn add(&mut self, key: T, count: u64) {
-        *self.0.entry(key).or_default() += count;
+    pub fn add(&mut self, key: T, count: C) {
+        self.update(key, |c| c.add_count(count));
     }
 
     pub fn inc(&mut self, key: T) {
-        self.add(key, 1);
+        self.add(key, C::ONE);
+    }
+
+    /// Replaces the count of `key` by `f` of it, zero if it was never seen.
+    pub fn update(&mut self, key: T, f: impl FnOnce(C) -> C) {
+        match self.0.entry(key) {
+            Entry::Occupied(mut e) => {
+                let c = f(*e.get());
+                if c == C::ZERO {
+                    e.remove();
+                } else {
+                    *e.get_mut() = c;
+                }
+            }
+            Entry::Vacant(e) => {
+                let c = f(C::ZERO);
+                if c != C::ZERO {
+                    e.insert(c);
+                }
+            }
+        }
+    }
+
+    /// Drops `key` and returns its count.
+    pub fn remove(&mut self, key: &T) -> C {
+        self.0.remove(key).unwrap_or(C::ZERO)
+    }
+
+    /// Keeps the keys for which `f` holds.
+    pub fn retain(&mut self, mut f: impl FnMut(&T, C) -> bool) {
+        self.0.retain(|k, c| f(k, *c));
+    }
+
+    /// The count of `key`, zero if it was never seen.
+    pub fn frequency(&self, key: &T) -> C {
+        self.0.get(key).copied().unwrap_or(C::ZERO)
     }
 
-    pub fn min(&self) -> (&T, &u64) {
+    /// The sum of all counts.
+    pub fn total(&self) -> C {
+        self.0.values().fold(C::ZERO, |a, b| a.add_count(*b))
+    }
+
+    /// Some key with the lowest count; which one on ties is unspecified. See
+    /// `least_common` for a deterministic choice.
+    pub fn min(&self) -> Option<(&T, &C)> {
         self.iter()
-            .reduce(|(s, c), (t, d)| if c < d { (s, c) } else { (t, d) })
-            .unwrap()
+            .reduce(|(s, c), (t, d)| if c <= d { (s, c) } else { (t, d) })
     }
 
-    pub fn max(&self) -> (&T, &u64) {
+    /// Some key with the highest count; which one on ties is unspecified. See
+    /// `most_common` for a deterministic choice.
+    pub fn max(&self) -> Option<(&T, &C)> {
         self.iter()
-            .reduce(|(s, c), (t, d)| if c > d { (s, c) } else { (t, d) })
-            .unwrap()
+            .reduce(|(s, c), (t, d)| if c >= d { (s, c) } else { (t, d) })
     }
 
-    pub fn count(&mut self, iter: impl Iterator<Item = T>) {
-        iter.for_each(|t| self.inc(t));
+    pub fn count(&mut self, iter: impl IntoIterator<Item = T>) {
+        iter.into_iter().for_each(|t| self.inc(t));
     }
 
-    pub fn add_from(&mut self, other: Self) {
-        for (k, v) in other.0 {
-            self.add(k, v);
+    pub fn add_from(&mut self, other: &Self)
+    where
+        T: Clone,
+    {
+        for (k, v) in other.iter() {
+            self.add(k.clone(), *v);
         }
     }
 }
 
-impl<T> Hash for Counter<T>
+impl<T: Hash + Ord, C: Count> Counter<T, C> {
+    /// The `n` highest counts, highest first. Ties go to the smaller key.
+    pub fn most_common(&self, n: usize) -> Vec<(&T, C)> {
+        self.ranked(n, |(s, c), (t, d)| d.cmp(c).then(s.cmp(t)))
+    }
+
+    /// The `n` lowest counts, lowest first. Ties go to the smaller key.
+    pub fn least_common(&self, n: usize) -> Vec<(&T, C)> {
+        self.ranked(n, |(s, c), (t, d)| c.cmp(d).then(s.cmp(t)))
+    }
+
+    fn ranked(
+        &self,
+        n: usize,
+        cmp: impl Fn(&(&T, C), &(&T, C)) -> std::cmp::Ordering,
+    ) -> Vec<(&T, C)> {
+        self.iter()
+            .map(|(k, v)| (k, *v))
+            .sorted_by(cmp)
+            .take(n)
+            .collect()
+    }
+}
+
+impl<T: Hash + Eq, C: Count> Default for Counter<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq, C: Count> FromIterator<T> for Counter<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.count(iter);
+        counter
+    }
+}
+
+impl<T: Hash + Eq, C: Count> Extend<T> for Counter<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.count(iter);
+    }
+}
+
+impl<T: Hash + Eq + Clone, C: Count> AddAssign<&Counter<T, C>> for Counter<T, C> {
+    fn add_assign(&mut self, rhs: &Counter<T, C>) {
+        self.add_from(rhs);
+    }
+}
+
+impl<T: Hash + Eq + Clone, C: Count> Add<&Counter<T, C>> for Counter<T, C> {
+    type Output = Self;
+    fn add(mut self, rhs: &Counter<T, C>) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+/// Unsigned counts saturate at zero, signed ones go negative.
+impl<T: Hash + Eq + Clone, C: Count> SubAssign<&Counter<T, C>> for Counter<T, C> {
+    fn sub_assign(&mut self, rhs: &Counter<T, C>) {
+        for (k, v) in rhs.iter() {
+            let c = self.0.entry(k.clone()).or_default();
+            *c = c.sub_count(*v);
+        }
+        self.0.retain(|_, c| *c != C::ZERO);
+    }
+}
+
+impl<T: Hash + Eq + Clone, C: Count> Sub<&Counter<T, C>> for Counter<T, C> {
+    type Output = Self;
+    fn sub(mut self, rhs: &Counter<T, C>) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T, C: Count> MulAssign<C> for Counter<T, C> {
+    fn mul_assign(&mut self, rhs: C) {
+        self.0.values_mut().for_each(|c| *c = *c * rhs);
+        self.0.retain(|_, c| *c != C::ZERO);
+    }
+}
+
+impl<T, C: Count> Mul<C> for Counter<T, C> {
+    type Output = Self;
+    fn mul(mut self, rhs: C) -> Self {
+        self *= rhs;
+        self
+    }
+}
+
+/// Most common first, e.g. `{a: 3, b: 1}`.
+impl<T: Hash + Ord + Display, C: Count + Display> Display for Counter<T, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self.most_common(self.len());
+        write!(f, "{{")?;
+        for (i, (k, v)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", k, v)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<T, C> Hash for Counter<T, C>
 where
     T: Hash + Ord,
+    C: Hash,
 {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         for (k, v) in self.0.iter().sorted_by_key(|(k, _)| *k) {
@@ -57,15 +254,66 @@ where
     }
 }
 
-impl<T> Deref for Counter<T> {
-    type Target = HashMap<T, u64>;
+impl<T, C> Deref for Counter<T, C> {
+    type Target = HashMap<T, C>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl<T> DerefMut for Counter<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+#[cfg(test)]
+mod test {
+    use super::Counter;
+
+    #[test]
+    fn test_counter() {
+        let a: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(a.total(), 11);
+        assert_eq!(a.frequency(&'a'), 5);
+        assert_eq!(a.frequency(&'z'), 0);
+        assert_eq!(a.max(), Some((&'a', &5)));
+        assert_eq!(a.most_common(3), vec![(&'a', 5), (&'b', 2), (&'r', 2)]);
+        assert_eq!(a.least_common(2), vec![(&'c', 1), (&'d', 1)]);
+        assert_eq!(a.to_string(), "{a: 5, b: 2, r: 2, c: 1, d: 1}");
+        assert_eq!(Counter::<char>::new().min(), None);
+
+        let mut b: Counter<char> = "bad".chars().collect();
+        b.extend("dd".chars());
+        assert_eq!((a.clone() - &b).to_string(), "{a: 4, r: 2, b: 1, c: 1}");
+        assert_eq!((b.clone() - &a).to_string(), "{d: 2}");
+        assert_eq!((b.clone() * 2 + &b).to_string(), "{d: 9, a: 3, b: 3}");
+
+        b.update('d', |c| c - 3);
+        b.update('z', |c| c + 2);
+        b.update('y', |c| c * 5);
+        assert_eq!(b.to_string(), "{z: 2, a: 1, b: 1}");
+        b.retain(|k, _| *k != 'a');
+        assert_eq!(b.remove(&'z'), 2);
+        assert_eq!(b.remove(&'z'), 0);
+        assert_eq!(b.to_string(), "{b: 1}");
+    }
+
+    #[test]
+    fn test_signed_counter() {
+        let a: Counter<char, i32> = "aab".chars().collect();
+        let b: Counter<char, i32> = "abbc".chars().collect();
+        assert_eq!((a.clone() - &b).to_string(), "{a: 1, b: -1, c: -1}");
+        assert_eq!((a.clone() - &b).total(), -1);
+        assert_eq!((a.clone() - &a).len(), 0);
+        assert_eq!((b * -2).least_common(1), vec![(&'b', -4)]);
+
+        let mut big: Counter<(), u128> = Counter::new();
+        big.add((), u64::MAX as u128);
+        big.add((), u64::MAX as u128);
+        assert_eq!(big.total(), 2 * u64::MAX as u128);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "count overflow")]
+    fn test_count_overflow() {
+        let mut c: Counter<(), u8> = Counter::new();
+        c.add((), 200);
+        c.add((), 100);
     }
 }
@@ -0,0 +1,117 @@
+// Multisets of states that evolve in lockstep.
+use std::hash::Hash;
+
+use crate::counter::{Count, Counter};
+
+/// A population of states with multiplicities. Every step replaces each state
+/// by its weighted successors, so `n` copies of a state with a successor of
+/// weight `w` become `n * w` copies of that successor. Absorbed states leave
+/// the population and are tallied separately.
+#[derive(Debug, Clone)]
+pub struct Evolution<S, C = u64> {
+    live: Counter<S, C>,
+    absorbed: Counter<S, C>,
+    steps: usize,
+}
+
+impl<S: Hash + Eq, C: Count> Evolution<S, C> {
+    pub fn new(initial: Counter<S, C>) -> Self {
+        Self {
+            live: initial,
+            absorbed: Counter::new(),
+            steps: 0,
+        }
+    }
+
+    /// The states still evolving.
+    pub fn live(&self) -> &Counter<S, C> {
+        &self.live
+    }
+
+    /// Every state that was absorbed so far.
+    pub fn absorbed(&self) -> &Counter<S, C> {
+        &self.absorbed
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Advances every live state once. Successors for which `absorbing` holds
+    /// are moved to the absorbed tally. Returns whether any state is left.
+    pub fn step<I>(&mut self, successors: impl Fn(&S) -> I, absorbing: impl Fn(&S) -> bool) -> bool
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        let mut next = Counter::new();
+        for (state, n) in self.live.iter() {
+            for (succ, weight) in successors(state) {
+                let tally = if absorbing(&succ) {
+                    &mut self.absorbed
+                } else {
+                    &mut next
+                };
+                tally.add(succ, *n * weight);
+            }
+        }
+        self.live = next;
+        self.steps += 1;
+        !self.live.is_empty()
+    }
+
+    /// Advances `n` steps without absorbing anything.
+    pub fn run<I>(&mut self, n: usize, successors: impl Fn(&S) -> I)
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        for _ in 0..n {
+            self.step(&successors, |_| false);
+        }
+    }
+
+    /// Steps until every state is absorbed, but at most `max_steps` times.
+    /// Returns whether everything was absorbed; states that cycle or never
+    /// reach an absorbing successor stay live.
+    pub fn run_until_absorbed<I>(
+        &mut self,
+        max_steps: usize,
+        successors: impl Fn(&S) -> I,
+        absorbing: impl Fn(&S) -> bool,
+    ) -> bool
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        for _ in 0..max_steps {
+            if self.live.is_empty() {
+                break;
+            }
+            self.step(&successors, &absorbing);
+        }
+        self.live.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Evolution;
+
+    #[test]
+    fn test_run_until_absorbed() {
+        // Walks right by one or two until reaching 3 or beyond.
+        let mut walk = Evolution::new([0].into_iter().collect());
+        assert!(walk.run_until_absorbed(10, |&x| [(x + 1, 1), (x + 2, 1)], |&x| x >= 3));
+        assert_eq!(walk.steps(), 3);
+        assert!(walk.live().is_empty());
+        assert_eq!(walk.absorbed().to_string(), "{3: 3, 4: 2}");
+
+        // Alternates between 0 and 1 without ever reaching 3.
+        let mut cycle = Evolution::new([0].into_iter().collect());
+        assert!(!cycle.run_until_absorbed(5, |&x: &u8| [(1 - x, 1)], |&x| x >= 3));
+        assert_eq!(cycle.steps(), 5);
+        assert_eq!(cycle.live().frequency(&1), 1);
+
+        let mut doubling = Evolution::<u8, u128>::new([0].into_iter().collect());
+        doubling.run(100, |&x| [(x, 2)]);
+        assert_eq!(doubling.live().frequency(&0), 1 << 100);
+    }
+}
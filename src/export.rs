@@ -0,0 +1,193 @@
+// Writing grids as binary PPM (colour) and PGM (greyscale) images.
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use crate::matrix::{MatGet, SparseGrid};
+
+pub type Rgb = [u8; 3];
+
+/// Writes `grid` as a P6 image, each cell a `scale`x`scale` block coloured by
+/// `colour`.
+pub fn write_ppm<'a, T: 'a>(
+    out: impl Write,
+    grid: &impl MatGet<'a, T>,
+    scale: usize,
+    colour: impl Fn(T) -> Rgb,
+) -> io::Result<()> {
+    write_raster(out, "P6", grid, scale, |t, buf| {
+        buf.extend_from_slice(&colour(t))
+    })
+}
+
+/// Writes `grid` as a P5 image, each cell a `scale`x`scale` block with the
+/// grey level returned by `shade`.
+pub fn write_pgm<'a, T: 'a>(
+    out: impl Write,
+    grid: &impl MatGet<'a, T>,
+    scale: usize,
+    shade: impl Fn(T) -> u8,
+) -> io::Result<()> {
+    write_raster(out, "P5", grid, scale, |t, buf| buf.push(shade(t)))
+}
+
+/// Writes the part of `grid` within its bounds as a P6 image.
+pub fn write_sparse_ppm<T: Copy + PartialEq>(
+    out: impl Write,
+    grid: &SparseGrid<T>,
+    scale: usize,
+    colour: impl Fn(T) -> Rgb,
+) -> io::Result<()> {
+    let mat = grid.to_mat();
+    write_ppm(out, &mat, scale, colour)
+}
+
+/// Writes the part of `grid` within its bounds as a P5 image.
+pub fn write_sparse_pgm<T: Copy + PartialEq>(
+    out: impl Write,
+    grid: &SparseGrid<T>,
+    scale: usize,
+    shade: impl Fn(T) -> u8,
+) -> io::Result<()> {
+    let mat = grid.to_mat();
+    write_pgm(out, &mat, scale, shade)
+}
+
+fn write_raster<'a, T: 'a>(
+    mut out: impl Write,
+    magic: &str,
+    grid: &impl MatGet<'a, T>,
+    scale: usize,
+    pixel: impl Fn(T, &mut Vec<u8>),
+) -> io::Result<()> {
+    assert!(scale > 0, "scale must be positive");
+    let (h, w) = (grid.height(), grid.width());
+    write!(out, "{}\n{} {}\n255\n", magic, w * scale, h * scale)?;
+    let mut row = Vec::new();
+    for i in 0..h {
+        row.clear();
+        for j in 0..w {
+            let start = row.len();
+            pixel(grid.get((i, j)), &mut row);
+            let end = row.len();
+            for _ in 1..scale {
+                row.extend_from_within(start..end);
+            }
+        }
+        for _ in 0..scale {
+            out.write_all(&row)?;
+        }
+    }
+    out.flush()
+}
+
+/// Writes one numbered image per call into a directory, e.g. once per step
+/// of a simulation. The frames are named `{prefix}00000.ppm` and so on, so
+/// they sort in order.
+pub struct FrameSink {
+    dir: PathBuf,
+    prefix: String,
+    scale: usize,
+    frame: usize,
+}
+
+impl FrameSink {
+    /// Creates `dir` if it does not exist yet.
+    pub fn new(dir: impl Into<PathBuf>, prefix: &str) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            prefix: prefix.to_string(),
+            scale: 1,
+            frame: 0,
+        })
+    }
+
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The number of frames written so far.
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+
+    pub fn ppm<'a, T: 'a>(
+        &mut self,
+        grid: &impl MatGet<'a, T>,
+        colour: impl Fn(T) -> Rgb,
+    ) -> io::Result<PathBuf> {
+        let (path, out) = self.next_file("ppm")?;
+        write_ppm(out, grid, self.scale, colour)?;
+        Ok(path)
+    }
+
+    pub fn pgm<'a, T: 'a>(
+        &mut self,
+        grid: &impl MatGet<'a, T>,
+        shade: impl Fn(T) -> u8,
+    ) -> io::Result<PathBuf> {
+        let (path, out) = self.next_file("pgm")?;
+        write_pgm(out, grid, self.scale, shade)?;
+        Ok(path)
+    }
+
+    fn next_file(&mut self, ext: &str) -> io::Result<(PathBuf, BufWriter<File>)> {
+        let path = self
+            .dir
+            .join(format!("{}{:05}.{}", self.prefix, self.frame, ext));
+        let out = BufWriter::new(File::create(&path)?);
+        self.frame += 1;
+        Ok((path, out))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{write_pgm, write_ppm, write_sparse_pgm, FrameSink};
+    use crate::matrix::{Mat, SparseGrid};
+
+    #[test]
+    fn test_write_images() {
+        let mat = Mat::from_rows(vec![vec![0u8, 1], vec![2, 3]]);
+
+        let mut pgm = Vec::new();
+        write_pgm(&mut pgm, &mat, 1, |v| v * 80).unwrap();
+        assert_eq!(pgm, b"P5\n2 2\n255\n\x00\x50\xa0\xf0");
+
+        let mut ppm = Vec::new();
+        write_ppm(&mut ppm, &mat, 2, |v| [v, 0, 9]).unwrap();
+        let (header, body) = ppm.split_at(11);
+        assert_eq!(header, b"P6\n4 4\n255\n");
+        let row0 = [0, 0, 9, 0, 0, 9, 1, 0, 9, 1, 0, 9];
+        let row1 = [2, 0, 9, 2, 0, 9, 3, 0, 9, 3, 0, 9];
+        assert_eq!(body, [row0, row0, row1, row1].concat());
+
+        let mut sparse = SparseGrid::new(false);
+        sparse.set((-1, 5), true);
+        sparse.set((0, 6), true);
+        let mut pgm = Vec::new();
+        write_sparse_pgm(&mut pgm, &sparse, 1, |b| if b { 255 } else { 0 }).unwrap();
+        assert_eq!(pgm, b"P5\n2 2\n255\n\xff\x00\x00\xff");
+    }
+
+    #[test]
+    fn test_frame_sink() {
+        let dir = std::env::temp_dir().join(format!("aoc21-frames-{}", std::process::id()));
+        let mut sink = FrameSink::new(&dir, "step").unwrap().scale(3);
+        let mat = Mat::new(1, 2, true);
+        let first = sink.pgm(&mat, |b| b as u8).unwrap();
+        let second = sink.ppm(&mat, |_| [1, 2, 3]).unwrap();
+        assert_eq!(sink.frames(), 2);
+        assert_eq!(first, dir.join("step00000.pgm"));
+        assert_eq!(second, dir.join("step00001.ppm"));
+        assert!(std::fs::read(&second)
+            .unwrap()
+            .starts_with(b"P6\n6 3\n255\n"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+// Connected regions of grid cells. Everything here keeps an explicit stack,
+// so region size is not limited by the call stack.
+use std::collections::HashSet;
+
+use crate::{
+    automaton::Neighbourhood,
+    matrix::{Ix, Mat, MatGet},
+};
+
+/// All cells reachable from `seed` through cells matching `pred`, including
+/// `seed` itself. Empty if `seed` does not match.
+pub fn flood_fill<'a, T: 'a>(
+    grid: &impl MatGet<'a, T>,
+    seed: Ix,
+    hood: Neighbourhood,
+    pred: impl Fn(T) -> bool,
+) -> HashSet<Ix> {
+    let mut seen = HashSet::new();
+    if !pred(grid.get(seed)) {
+        return seen;
+    }
+    let mut stack = vec![seed];
+    seen.insert(seed);
+    while let Some(ix) = stack.pop() {
+        for &dir in hood.dirs() {
+            if let Some(n) = grid.step(ix, dir) {
+                if !seen.contains(&n) && pred(grid.get(n)) {
+                    seen.insert(n);
+                    stack.push(n);
+                }
+            }
+        }
+    }
+    seen
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Component {
+    pub size: usize,
+    /// Inclusive top left and bottom right corners.
+    pub bounds: (Ix, Ix),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    /// Index into `components` for every matching cell.
+    pub labels: Mat<Option<usize>>,
+    /// In order of their first cell in reading order.
+    pub components: Vec<Component>,
+}
+
+/// Split the cells matching `pred` into connected components.
+pub fn label_components<'a, T: 'a>(
+    grid: &impl MatGet<'a, T>,
+    hood: Neighbourhood,
+    pred: impl Fn(T) -> bool,
+) -> Components {
+    let mut labels = Mat::new(grid.height(), grid.width(), None);
+    let mut components = Vec::new();
+    let mut stack = Vec::new();
+
+    for seed in grid.iter_idx() {
+        if labels[seed].is_some() || !pred(grid.get(seed)) {
+            continue;
+        }
+        let label = Some(components.len());
+        let mut comp = Component {
+            size: 0,
+            bounds: (seed, seed),
+        };
+        labels[seed] = label;
+        stack.push(seed);
+        while let Some(ix @ (i, j)) = stack.pop() {
+            comp.size += 1;
+            let ((li, lj), (hi, hj)) = comp.bounds;
+            comp.bounds = ((li.min(i), lj.min(j)), (hi.max(i), hj.max(j)));
+            for &dir in hood.dirs() {
+                if let Some(n) = grid.step(ix, dir) {
+                    if labels[n].is_none() && pred(grid.get(n)) {
+                        labels[n] = label;
+                        stack.push(n);
+                    }
+                }
+            }
+        }
+        components.push(comp);
+    }
+
+    Components { labels, components }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{flood_fill, label_components, Component};
+    use crate::automaton::Neighbourhood::{Eight, Four};
+    use crate::input::{AoCInput, Chars};
+    use crate::matrix::{Mat, Torus};
+
+    const GRID: &str = "##..#\n#...#\n..#..\n.#..#\n";
+
+    #[test]
+    fn test_label_components() {
+        let Chars(mat) = Chars::<Mat<char>>::from_input(GRID);
+        let comps = label_components(&mat, Four, |c| c == '#');
+        assert_eq!(comps.components.len(), 5);
+        assert_eq!(
+            comps.components[0],
+            Component {
+                size: 3,
+                bounds: ((0, 0), (1, 1))
+            }
+        );
+        assert_eq!(comps.labels[(3, 4)], Some(4));
+        assert_eq!(comps.labels[(0, 2)], None);
+
+        let comps = label_components(&mat, Eight, |c| c == '#');
+        let sizes = comps.components.iter().map(|c| c.size).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![3, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let Chars(mat) = Chars::<Mat<char>>::from_input(GRID);
+        assert_eq!(flood_fill(&mat, (0, 2), Four, |c| c == '.').len(), 12);
+        assert!(flood_fill(&mat, (0, 0), Four, |c| c == '.').is_empty());
+        // The right column connects to the left one around the torus.
+        let Chars(tor) = Chars::<Torus<char>>::from_input(GRID);
+        assert_eq!(flood_fill(&tor, (0, 0), Four, |c| c == '#').len(), 7);
+    }
+}
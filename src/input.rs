@@ -64,20 +64,234 @@ where
     }
 }
 
-impl AoCLineInput for Vec<u8> {
-    fn from_line(s: &str) -> Self {
-        s.chars()
-            .map(|c| c.to_digit(16).unwrap() as u8)
-            .collect_vec()
+// Per-character line types. The element radix is part of the type, so
+// `Digits<Vec<u8>>` rejects `a-f` while `HexDigits<Vec<u8>>` accepts them.
+// The grid variants (e.g. `Digits<Mat<u8>>`) live in `matrix`.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharError {
+    pub c: char,
+    pub col: usize,
+    pub expected: &'static str,
+}
+
+impl std::fmt::Display for CharError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid character {:?} at column {}, expected {}",
+            self.c, self.col, self.expected
+        )
     }
 }
 
-impl AoCLineInput for Vec<u32> {
-    fn from_line(s: &str) -> Self {
-        s.chars().map(|c| c.to_digit(16).unwrap()).collect_vec()
+impl std::error::Error for CharError {}
+
+fn parse_chars<T>(
+    s: &str,
+    expected: &'static str,
+    f: impl Fn(char) -> Option<T>,
+) -> Result<Vec<T>, CharError> {
+    s.chars()
+        .enumerate()
+        .map(|(col, c)| f(c).ok_or(CharError { c, col, expected }))
+        .collect()
+}
+
+/// `CharError` in the given row of a multi-line input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridCharError {
+    pub row: usize,
+    pub err: CharError,
+}
+
+impl std::fmt::Display for GridCharError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}: {}", self.row, self.err)
+    }
+}
+
+impl std::error::Error for GridCharError {}
+
+/// Types with a one-character form, e.g. the tiles of a map. See `char_enum!`
+/// to define an enum with one.
+pub trait FromChar: Sized {
+    /// Describes the accepted characters in errors.
+    const EXPECTED: &'static str;
+
+    fn from_char(c: char) -> Option<Self>;
+}
+
+impl FromChar for char {
+    const EXPECTED: &'static str = "any character";
+
+    fn from_char(c: char) -> Option<Self> {
+        Some(c)
+    }
+}
+
+impl FromChar for bool {
+    const EXPECTED: &'static str = "'#' or '.'";
+
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
     }
 }
 
+pub fn parse_char_line<T: FromChar>(s: &str) -> Result<Vec<T>, CharError> {
+    parse_chars(s, T::EXPECTED, T::from_char)
+}
+
+/// One row per line, one element per character.
+pub fn parse_char_grid<T: FromChar>(s: &str) -> Result<Vec<Vec<T>>, GridCharError> {
+    s.lines()
+        .enumerate()
+        .map(|(row, line)| parse_char_line(line).map_err(|err| GridCharError { row, err }))
+        .collect()
+}
+
+/// Defines a fieldless enum whose variants stand for characters, with
+/// `FromChar`, `ToChar` and `Display` going between the two:
+/// `char_enum! { enum Tile { Wall = '#', Open = '.' } }`.
+#[macro_export]
+macro_rules! char_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $( $variant:ident = $c:literal ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $variant ),*
+        }
+
+        impl $crate::input::FromChar for $name {
+            const EXPECTED: &'static str = concat!("one of" $(, " ", stringify!($c))*);
+
+            fn from_char(c: char) -> Option<Self> {
+                match c {
+                    $( $c => Some($name::$variant), )*
+                    _ => None,
+                }
+            }
+        }
+
+        impl $crate::render::ToChar for $name {
+            fn to_char(&self) -> char {
+                match self {
+                    $( $name::$variant => $c, )*
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", $crate::render::ToChar::to_char(self))
+            }
+        }
+    };
+}
+
+/// Line of decimal digits, one element per character.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Digits<T>(pub T);
+
+/// Line of hexadecimal digits, one element per character.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HexDigits<T>(pub T);
+
+/// Line of `0`/`1` characters.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bits<T = Vec<bool>>(pub T);
+
+/// Line split into its characters.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chars<T = Vec<char>>(pub T);
+
+impl<T: From<u8>> Digits<Vec<T>> {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        parse_chars(s, "a decimal digit", |c| {
+            c.to_digit(10).map(|d| T::from(d as u8))
+        })
+        .map(Self)
+    }
+}
+
+impl<T: From<u8>> HexDigits<Vec<T>> {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        parse_chars(s, "a hex digit", |c| {
+            c.to_digit(16).map(|d| T::from(d as u8))
+        })
+        .map(Self)
+    }
+}
+
+impl Bits {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        parse_chars(s, "'0' or '1'", |c| match c {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        })
+        .map(Self)
+    }
+}
+
+impl Chars {
+    pub fn try_from_line(s: &str) -> Result<Self, CharError> {
+        Ok(Self(s.chars().collect_vec()))
+    }
+}
+
+macro_rules! char_line_input {
+    ( $( $ty:ty $(where $el:ident: $bound:path)? ),* ) => {
+        $(
+            impl$(<$el: $bound>)? AoCLineInput for $ty {
+                fn from_line(s: &str) -> Self {
+                    Self::try_from_line(s).unwrap_or_else(|e| panic!("{} in line {:?}", e, s))
+                }
+            }
+        )*
+    };
+}
+
+char_line_input!(
+    Digits<Vec<T>> where T: From<u8>,
+    HexDigits<Vec<T>> where T: From<u8>,
+    Bits,
+    Chars
+);
+
+macro_rules! deref_newtype {
+    ( $($name:ident),* ) => {
+        $(
+            impl<T> Deref for $name<T> {
+                type Target = T;
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl<T> DerefMut for $name<T> {
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    &mut self.0
+                }
+            }
+        )*
+    };
+}
+
+deref_newtype!(Digits, HexDigits, Bits, Chars);
+
 #[repr(transparent)]
 #[derive(Debug)]
 pub struct CSVLine<T>(pub Vec<T>);
@@ -128,3 +342,42 @@ pub fn sections_tuple<'s, T, B>(
     let (t, b) = s.split_once("\n\n").unwrap();
     (top(t), bot(b))
 }
+
+#[cfg(test)]
+mod test {
+    use super::AoCInput;
+    use super::{parse_char_grid, CharError, FromChar, GridCharError};
+    use crate::matrix::{Mat, MatGet};
+
+    crate::char_enum! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Tile {
+            Wall = '#',
+            Open = '.',
+        }
+    }
+
+    #[test]
+    fn test_char_enum() {
+        assert_eq!(Tile::from_char('#'), Some(Tile::Wall));
+        assert_eq!(Tile::from_char('x'), None);
+        let mat = Mat::<Tile>::from_input("#.\n.#\n");
+        assert_eq!(mat.get((0, 1)), Tile::Open);
+        assert_eq!(mat.render().to_string(), "#.\n.#\n");
+    }
+
+    #[test]
+    fn test_char_error_position() {
+        assert_eq!(
+            parse_char_grid::<Tile>("#.\n.x\n"),
+            Err(GridCharError {
+                row: 1,
+                err: CharError {
+                    c: 'x',
+                    col: 1,
+                    expected: "one of '#' '.'"
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,216 @@
+// Sets of integers stored as sorted, disjoint, inclusive ranges.
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+
+use crate::input::AoCLineInput;
+
+/// Integers usable as interval endpoints.
+pub trait Endpoint: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + From<u8> {}
+impl<T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>> Endpoint for T {}
+
+/// Maps the start of every range to its (inclusive) end. Ranges never
+/// overlap or touch, so every set has exactly one representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T> {
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of disjoint ranges.
+    pub fn num_ranges(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Number of integers in the set.
+    pub fn total_len(&self) -> T {
+        self.iter()
+            .fold(T::from(0), |acc, (lo, hi)| acc + (hi - lo) + T::from(1))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.ranges.iter().map(|(lo, hi)| (*lo, *hi))
+    }
+
+    /// Ranges between consecutive ranges of the set.
+    pub fn gaps(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.iter()
+            .tuple_windows()
+            .map(|((_, a), (b, _))| (a + T::from(1), b - T::from(1)))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first_key_value().map(|(lo, _)| *lo)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last_key_value().map(|(_, hi)| *hi)
+    }
+
+    /// The range containing `t`, if any.
+    pub fn range_of(&self, t: T) -> Option<(T, T)> {
+        self.ranges
+            .range(..=t)
+            .next_back()
+            .filter(|(_, hi)| t <= **hi)
+            .map(|(lo, hi)| (*lo, *hi))
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        self.range_of(t).is_some()
+    }
+
+    fn touches(hi: T, lo: T) -> bool {
+        lo <= hi || lo - hi == T::from(1)
+    }
+
+    /// Add `lo..=hi`, merging with all ranges it overlaps or touches.
+    pub fn insert(&mut self, mut lo: T, mut hi: T) {
+        assert!(lo <= hi, "empty range");
+        if let Some((&s, &e)) = self.ranges.range(..=lo).next_back() {
+            if Self::touches(e, lo) {
+                self.ranges.remove(&s);
+                lo = s;
+                hi = hi.max(e);
+            }
+        }
+        while let Some((&s, &e)) = self.ranges.range(lo..).next() {
+            if !Self::touches(hi, s) {
+                break;
+            }
+            self.ranges.remove(&s);
+            hi = hi.max(e);
+        }
+        self.ranges.insert(lo, hi);
+    }
+
+    /// Remove `lo..=hi`, splitting ranges that stick out on either side.
+    pub fn remove(&mut self, lo: T, hi: T) {
+        assert!(lo <= hi, "empty range");
+        if let Some((&s, &e)) = self.ranges.range(..lo).next_back() {
+            if e >= lo {
+                self.ranges.insert(s, lo - T::from(1));
+                if e > hi {
+                    self.ranges.insert(hi + T::from(1), e);
+                }
+            }
+        }
+        while let Some((&s, &e)) = self.ranges.range(lo..).next() {
+            if s > hi {
+                break;
+            }
+            self.ranges.remove(&s);
+            if e > hi {
+                self.ranges.insert(hi + T::from(1), e);
+            }
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        other.iter().for_each(|(lo, hi)| set.insert(lo, hi));
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = Self::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(&(a_lo, a_hi)), Some(&(b_lo, b_hi))) = (a.peek(), b.peek()) {
+            let (lo, hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+            if lo <= hi {
+                set.ranges.insert(lo, hi);
+            }
+            if a_hi < b_hi {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        set
+    }
+}
+
+impl<T: Endpoint> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|(lo, hi)| set.insert(lo, hi));
+        set
+    }
+}
+
+impl<T: Display> Display for IntervalSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges = self.ranges.iter().map(|(lo, hi)| format!("{}..{}", lo, hi));
+        write!(f, "{}", ranges.format(","))
+    }
+}
+
+/// Parses comma separated inclusive ranges `a..b`. A single number `a` is
+/// read as `a..a`.
+impl<T> AoCLineInput for IntervalSet<T>
+where
+    T: Endpoint + FromStr,
+    T::Err: std::fmt::Debug,
+{
+    fn from_line(s: &str) -> Self {
+        s.split(',')
+            .map(|range| {
+                let range = range.trim();
+                match range.split_once("..") {
+                    Some((lo, hi)) => (lo.parse().unwrap(), hi.parse().unwrap()),
+                    None => {
+                        let t = range.parse().unwrap();
+                        (t, t)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IntervalSet;
+    use crate::input::AoCLineInput;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = IntervalSet::<i64>::from_line("1..3,5..7,10..12");
+        set.insert(4, 4);
+        assert_eq!(set.to_string(), "1..7,10..12");
+        set.insert(8, 20);
+        assert_eq!(set.to_string(), "1..20");
+        set.remove(3, 5);
+        set.remove(10, 10);
+        assert_eq!(set.to_string(), "1..2,6..9,11..20");
+        assert_eq!(set.total_len(), 16);
+        assert!(set.contains(6) && !set.contains(10) && !set.contains(0));
+        assert_eq!(set.gaps().collect_vec(), vec![(3, 5), (10, 10)]);
+        set.remove(0, 100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let a = IntervalSet::<u32>::from_line("0..10,20..30");
+        let b = IntervalSet::<u32>::from_line("5..25,28");
+        assert_eq!(a.intersection(&b).to_string(), "5..10,20..25,28..28");
+        assert_eq!(a.union(&b).to_string(), "0..30");
+    }
+}
@@ -1,7 +1,19 @@
+pub mod aabb;
+pub mod automaton;
+pub mod bits;
+pub mod boundary;
 pub mod counter;
+pub mod evolve;
+pub mod export;
+pub mod flood;
 pub mod input;
+pub mod interval;
 pub mod matrix;
 pub mod pos;
+pub mod render;
+pub mod search;
+pub mod segment;
+pub mod view;
 
 #[macro_export]
 macro_rules! simple_main {
@@ -1,21 +1,48 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 
-use crate::input::AoCInput;
+use crate::{
+    boundary::{Boundary, Bounded, Wrapping},
+    input::{
+        parse_char_grid, AoCInput, Bits, CharError, Chars, Digits, FromChar, GridCharError,
+        HexDigits,
+    },
+    pos::{Dir4, Dir8},
+};
 
 pub type Ix = (usize, usize);
 
-trait MatEl {}
-impl MatEl for char {}
-impl MatEl for u8 {}
-impl MatEl for u16 {}
-impl MatEl for u32 {}
-impl MatEl for u64 {}
-impl MatEl for usize {}
-impl MatEl for i8 {}
-impl MatEl for i16 {}
-impl MatEl for i32 {}
-impl MatEl for i64 {}
-impl MatEl for isize {}
+/// Row and column of a cell in a grid without edges.
+pub type SignedIx = (isize, isize);
+
+/// How a grid of these is written in the input.
+pub trait ParseGrid: Sized {
+    /// Panics on malformed input.
+    fn parse_rows(s: &str) -> Vec<Vec<Self>>;
+}
+
+/// Numbers are separated by whitespace.
+macro_rules! whitespace_grid {
+    ( $($t:ty),* ) => {
+        $(
+            impl ParseGrid for $t {
+                fn parse_rows(s: &str) -> Vec<Vec<Self>> {
+                    parse_whitespace_rows(s)
+                }
+            }
+        )*
+    };
+}
+
+whitespace_grid!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Everything else has one character per element.
+impl<T: FromChar> ParseGrid for T {
+    fn parse_rows(s: &str) -> Vec<Vec<Self>> {
+        parse_char_grid(s).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
 
 pub trait MatGet<'a, T: 'a> {
     fn width(&self) -> usize;
@@ -31,21 +58,39 @@ pub trait MatGet<'a, T: 'a> {
         self.iter_idx().map(|pos| (pos, self.get(pos)))
     }
 
-    fn iter_bordering_idx(&self, (i, j): Ix) -> impl Iterator<Item = Ix> {
-        let mut pos = Vec::new();
-        if i > 0 {
-            pos.push((i - 1, j))
-        }
-        if j > 0 {
-            pos.push((i, j - 1))
-        }
-        if i + 1 < self.height() {
-            pos.push((i + 1, j))
-        }
-        if j + 1 < self.width() {
-            pos.push((i, j + 1))
-        }
-        pos.into_iter()
+    fn contains(&self, (i, j): Ix) -> bool {
+        i < self.height() && j < self.width()
+    }
+
+    fn get_checked(&self, ix: Ix) -> Option<T> {
+        self.contains(ix).then(|| self.get(ix))
+    }
+
+    /// Element at a possibly negative index, if that is inside the matrix.
+    fn get_signed(&self, (i, j): SignedIx) -> Option<T> {
+        self.get_checked((usize::try_from(i).ok()?, usize::try_from(j).ok()?))
+    }
+
+    /// Index `(di, dj)` away from `ix`, if that is still inside the matrix.
+    fn offset(&self, (i, j): Ix, (di, dj): SignedIx) -> Option<Ix> {
+        let ix = (i.checked_add_signed(di)?, j.checked_add_signed(dj)?);
+        self.contains(ix).then_some(ix)
+    }
+
+    /// Index one step in the given direction, if that is still inside the matrix.
+    fn step(&self, ix: Ix, dir: impl Into<Dir8>) -> Option<Ix> {
+        let d = dir.into().delta::<isize>();
+        self.offset(ix, (d.y, d.x))
+    }
+
+    /// Neighbours that `step` resolves to, clockwise from north. Boundaries
+    /// like `Clamped` map some steps off an edge cell back onto the cell
+    /// itself; those are left out.
+    fn iter_bordering_idx(&self, ix: Ix) -> impl Iterator<Item = Ix> {
+        Dir4::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(ix, d))
+            .filter(move |&n| n != ix)
     }
 
     fn iter_bordering(&'a self, pos: Ix) -> impl Iterator<Item = (Ix, T)> {
@@ -56,33 +101,12 @@ pub trait MatGet<'a, T: 'a> {
         self.iter_bordering_idx(pos).map(|pos| self.get(pos))
     }
 
-    fn iter_diag_bordering_idx(&self, (i, j): Ix) -> impl Iterator<Item = Ix> {
-        let mut pos = Vec::new();
-        if i > 0 {
-            pos.push((i - 1, j));
-            if j > 0 {
-                pos.push((i - 1, j - 1));
-            }
-            if j + 1 < self.width() {
-                pos.push((i - 1, j + 1));
-            }
-        }
-        if j > 0 {
-            pos.push((i, j - 1));
-        }
-        if i + 1 < self.height() {
-            pos.push((i + 1, j));
-            if j > 0 {
-                pos.push((i + 1, j - 1));
-            }
-            if j + 1 < self.width() {
-                pos.push((i + 1, j + 1));
-            }
-        }
-        if j + 1 < self.width() {
-            pos.push((i, j + 1));
-        }
-        pos.into_iter()
+    /// Like `iter_bordering_idx`, including the diagonals.
+    fn iter_diag_bordering_idx(&self, ix: Ix) -> impl Iterator<Item = Ix> {
+        Dir8::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(ix, d))
+            .filter(move |&n| n != ix)
     }
 
     fn iter_diag_bordering(&'a self, pos: Ix) -> impl Iterator<Item = (Ix, T)> {
@@ -95,168 +119,967 @@ pub trait MatGet<'a, T: 'a> {
     }
 }
 
-pub trait MatMut<'a, T: 'a> {
+/// `T: 'a` follows from `&'a mut self`, so it is not spelled out. That lets
+/// `for<'a> MatMut<'a, T>` hold for borrowed `T` as well.
+pub trait MatMut<'a, T> {
     fn get_mut(&'a mut self, ix: Ix) -> &'a mut T;
 }
 
-#[derive(Debug, Clone)]
-pub struct Mat<T> {
-    pub mat: Vec<Vec<T>>,
-    pub m: usize,
-    pub n: usize,
+/// Rows of a grid did not all have the same length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaggedRows {
+    pub row: usize,
+    pub len: usize,
+    pub expected: usize,
 }
 
-impl<'a, T: Copy + 'a> MatGet<'a, T> for Mat<T> {
-    fn height(&self) -> usize {
-        self.m
-    }
-
-    fn width(&self) -> usize {
-        self.n
-    }
-
-    fn get(&self, (i, j): Ix) -> T {
-        self.mat[i][j]
+impl std::fmt::Display for RaggedRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "row {} has {} elements, expected {}",
+            self.row, self.len, self.expected
+        )
     }
 }
 
-impl<'a, T: 'a> MatMut<'a, T> for Mat<T> {
-    fn get_mut(&mut self, (i, j): Ix) -> &mut T {
-        &mut self.mat[i][j]
+impl std::error::Error for RaggedRows {}
+
+/// Concatenate `rows` into row-major storage, returning it with its height and width.
+fn flatten<T>(rows: Vec<Vec<T>>) -> Result<(Vec<T>, usize, usize), RaggedRows> {
+    let (height, width) = (rows.len(), rows.first().map_or(0, |r| r.len()));
+    let mut data = Vec::with_capacity(height * width);
+    for (row, r) in rows.into_iter().enumerate() {
+        if r.len() != width {
+            return Err(RaggedRows {
+                row,
+                len: r.len(),
+                expected: width,
+            });
+        }
+        data.extend(r);
     }
+    Ok((data, height, width))
 }
 
-impl<T> AoCInput for Mat<T>
+fn parse_whitespace_rows<T>(s: &str) -> Vec<Vec<T>>
 where
-    T: MatEl + std::str::FromStr,
+    T: std::str::FromStr,
     <T as std::str::FromStr>::Err: std::fmt::Debug,
 {
-    fn from_input(s: &str) -> Self {
-        let mat = s
-            .lines()
-            .map(|line| {
-                if line.contains(" ") {
-                    line.split_whitespace()
-                        .map(|num| num.parse().unwrap())
-                        .collect_vec()
-                } else {
-                    // Assume one character for every element.
-                    line.chars()
-                        .map(|num| num.to_string().parse().unwrap())
-                        .collect_vec()
-                }
-            })
-            .collect_vec();
-        let (m, n) = (mat.len(), mat[0].len());
-        Self { mat, m, n }
+    s.lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|num| num.parse().unwrap())
+                .collect_vec()
+        })
+        .collect_vec()
+}
+
+/// Grid stored row-major in a single `Vec`. The boundary policy `B` decides
+/// what lies beyond the edges, see `boundary`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T, B = Bounded> {
+    data: Vec<T>,
+    height: usize,
+    width: usize,
+    boundary: B,
+}
+
+/// Grid without anything beyond its edges.
+pub type Mat<T> = Grid<T, Bounded>;
+
+/// Grid where stepping off one edge wraps around to the opposite one.
+pub type Torus<T> = Grid<T, Wrapping>;
+
+impl<T, B: Default> Grid<T, B> {
+    /// Wrap row-major `data`. Panics if it does not hold `height * width` elements.
+    pub fn from_vec(height: usize, width: usize, data: Vec<T>) -> Self {
+        assert_eq!(
+            data.len(),
+            height * width,
+            "expected {}x{} elements",
+            height,
+            width
+        );
+        Self {
+            data,
+            height,
+            width,
+            boundary: B::default(),
+        }
+    }
+
+    pub fn try_from_rows(rows: Vec<Vec<T>>) -> Result<Self, RaggedRows> {
+        let (data, height, width) = flatten(rows)?;
+        Ok(Self::from_vec(height, width, data))
+    }
+
+    /// Panics if the rows do not all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        Self::try_from_rows(rows).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn new(height: usize, width: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_vec(height, width, vec![fill; height * width])
+    }
+
+    pub fn from_fn(height: usize, width: usize, f: impl FnMut(Ix) -> T) -> Self {
+        Self::from_vec(height, width, Self::collect(height, width, f))
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Torus<T> {
-    pub mat: Vec<Vec<T>>,
-    pub m: usize,
-    pub n: usize,
+impl<T, B> Grid<T, B> {
+    fn collect<U>(height: usize, width: usize, mut f: impl FnMut(Ix) -> U) -> Vec<U> {
+        (0..height)
+            .flat_map(|i| (0..width).map(move |j| (i, j)))
+            .map(&mut f)
+            .collect()
+    }
+
+    /// New grid with the same boundary, filled by `f`.
+    fn rebuild<U>(&self, height: usize, width: usize, f: impl FnMut(Ix) -> U) -> Grid<U, B>
+    where
+        B: Clone,
+    {
+        Grid {
+            data: Self::collect(height, width, f),
+            height,
+            width,
+            boundary: self.boundary.clone(),
+        }
+    }
+
+    pub fn boundary(&self) -> &B {
+        &self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: B) {
+        self.boundary = boundary;
+    }
+
+    /// The same cells under another boundary policy.
+    pub fn with_boundary<C>(self, boundary: C) -> Grid<T, C> {
+        Grid {
+            data: self.data,
+            height: self.height,
+            width: self.width,
+            boundary,
+        }
+    }
+
+    fn flat_index(&self, (i, j): Ix) -> usize {
+        assert!(
+            i < self.height && j < self.width,
+            "index {:?} out of bounds for {}x{} grid",
+            (i, j),
+            self.height,
+            self.width
+        );
+        i * self.width + j
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.width..(i + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[i * self.width..(i + 1) * self.width]
+    }
+
+    /// Also yields `height` empty rows for grids without columns.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|i| self.row(i))
+    }
+
+    pub fn col(&self, j: usize) -> impl Iterator<Item = &T> {
+        assert!(j < self.width, "column {} out of bounds", j);
+        self.data.iter().skip(j).step_by(self.width)
+    }
+
+    /// All elements in row-major order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U, B>
+    where
+        B: Clone,
+    {
+        self.rebuild(self.height, self.width, |ix| f(&self[ix]))
+    }
+
+    /// The `height x width` block whose top left corner is at `(i, j)`.
+    pub fn sub_mat(&self, (i, j): Ix, height: usize, width: usize) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        assert!(
+            i + height <= self.height && j + width <= self.width,
+            "{}x{} block at {:?} does not fit into {}x{} grid",
+            height,
+            width,
+            (i, j),
+            self.height,
+            self.width
+        );
+        self.rebuild(height, width, |(di, dj)| self[(i + di, j + dj)].clone())
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        self.rebuild(self.width, self.height, |(i, j)| self[(j, i)].clone())
+    }
+
+    /// Rotate a quarter turn clockwise: the first column becomes the first row.
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        self.rebuild(self.width, self.height, |(i, j)| {
+            self[(self.height - 1 - j, i)].clone()
+        })
+    }
+
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        self.rebuild(self.width, self.height, |(i, j)| {
+            self[(j, self.width - 1 - i)].clone()
+        })
+    }
+
+    /// Mirror left to right.
+    pub fn flip_h(&self) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        self.rebuild(self.height, self.width, |(i, j)| {
+            self[(i, self.width - 1 - j)].clone()
+        })
+    }
+
+    /// Mirror top to bottom.
+    pub fn flip_v(&self) -> Self
+    where
+        T: Clone,
+        B: Clone,
+    {
+        self.rebuild(self.height, self.width, |(i, j)| {
+            self[(self.height - 1 - i, j)].clone()
+        })
+    }
 }
 
 impl<T> Torus<T> {
-    pub fn east(&self, (i, j): Ix) -> Ix {
-        (i, (j + 1) % self.n)
+    pub fn wrapping_step(&self, (i, j): Ix, dir: impl Into<Dir8>) -> Ix {
+        let d = dir.into().delta::<isize>();
+        let ix = (i as isize + d.y, j as isize + d.x);
+        Boundary::<T>::resolve(&Wrapping, ix, self.height, self.width).unwrap()
     }
 
-    pub fn west(&self, (i, j): Ix) -> Ix {
-        (i, (j + self.n - 1) % self.n)
+    pub fn east(&self, ix: Ix) -> Ix {
+        self.wrapping_step(ix, Dir4::East)
     }
 
-    pub fn south(&self, (i, j): Ix) -> Ix {
-        ((i + 1) % self.m, j)
+    pub fn west(&self, ix: Ix) -> Ix {
+        self.wrapping_step(ix, Dir4::West)
     }
 
-    pub fn north(&self, (i, j): Ix) -> Ix {
-        ((i + self.m - 1) % self.m, j)
+    pub fn south(&self, ix: Ix) -> Ix {
+        self.wrapping_step(ix, Dir4::South)
+    }
+
+    pub fn north(&self, ix: Ix) -> Ix {
+        self.wrapping_step(ix, Dir4::North)
+    }
+}
+
+impl<T, B> std::ops::Index<Ix> for Grid<T, B> {
+    type Output = T;
+
+    fn index(&self, ix: Ix) -> &T {
+        &self.data[self.flat_index(ix)]
     }
 }
 
-impl<'a, T: Copy + 'a> MatGet<'a, T> for Torus<T> {
+impl<T, B> std::ops::IndexMut<Ix> for Grid<T, B> {
+    fn index_mut(&mut self, ix: Ix) -> &mut T {
+        let k = self.flat_index(ix);
+        &mut self.data[k]
+    }
+}
+
+impl<'a, T: Copy + 'a, B: Boundary<T>> MatGet<'a, T> for Grid<T, B> {
     fn height(&self) -> usize {
-        self.m
+        self.height
     }
 
     fn width(&self) -> usize {
-        self.n
+        self.width
     }
 
-    fn get(&self, (i, j): Ix) -> T {
-        self.mat[i][j]
+    fn get(&self, ix: Ix) -> T {
+        self[ix]
     }
 
-    fn iter_bordering_idx(&self, (i, j): Ix) -> impl Iterator<Item = Ix> {
-        [
-            ((i + self.m - 1) % self.m, j),
-            ((i + 1) % self.m, j),
-            (i, (j + self.n - 1) % self.n),
-            (i, (j + 1) % self.n),
-        ]
-        .into_iter()
+    fn get_signed(&self, ix: SignedIx) -> Option<T> {
+        match self.boundary.resolve(ix, self.height, self.width) {
+            Some(ix) => Some(self[ix]),
+            None => self.boundary.outside(),
+        }
     }
 
-    fn iter_diag_bordering_idx(&self, (i, j): Ix) -> impl Iterator<Item = Ix> {
-        let (il, ir) = ((i + self.m - 1) % self.m, (i + 1) % self.m);
-        let (jl, jr) = ((j + self.n - 1) % self.n, (j + 1) % self.n);
-        [
-            (il, jl),
-            (il, j),
-            (il, jr),
-            (i, jl),
-            (i, jr),
-            (ir, jl),
-            (ir, j),
-            (ir, jr),
-        ]
-        .into_iter()
+    fn offset(&self, (i, j): Ix, (di, dj): SignedIx) -> Option<Ix> {
+        let ix = (i as isize + di, j as isize + dj);
+        self.boundary.resolve(ix, self.height, self.width)
     }
 }
 
-impl<'a, T: 'a> MatMut<'a, T> for Torus<T> {
-    fn get_mut(&mut self, (i, j): Ix) -> &mut T {
-        &mut self.mat[i][j]
+impl<'a, T, B> MatMut<'a, T> for Grid<T, B> {
+    fn get_mut(&mut self, ix: Ix) -> &mut T {
+        &mut self[ix]
     }
 }
 
-impl<T> AoCInput for Torus<T>
-where
-    T: MatEl + std::str::FromStr,
-    <T as std::str::FromStr>::Err: std::fmt::Debug,
-{
+impl<T: ParseGrid, B: Default> AoCInput for Grid<T, B> {
     fn from_input(s: &str) -> Self {
-        let mat = s
-            .lines()
-            .map(|line| {
-                if line.contains(" ") {
-                    line.split_whitespace()
-                        .map(|num| num.parse().unwrap())
-                        .collect_vec()
-                } else {
-                    // Assume one character for every element.
-                    line.chars()
-                        .map(|num| num.to_string().parse().unwrap())
-                        .collect_vec()
+        Self::try_from_rows(T::parse_rows(s)).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// One row per line with the elements back to back, which round-trips for
+/// one character per element through the matching char grid (`Digits`,
+/// `Chars`, ...). See `separated` for wider elements.
+impl<T: std::fmt::Display, B> std::fmt::Display for Grid<T, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.separated("").fmt(f)
+    }
+}
+
+/// Unbounded grid where every cell holds `background` unless set otherwise.
+/// Only the other cells are stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SignedIx, T>,
+    background: T,
+    bounds: Option<(SignedIx, SignedIx)>,
+}
+
+impl<T: PartialEq> SparseGrid<T> {
+    pub fn new(background: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            background,
+            bounds: None,
+        }
+    }
+
+    /// Copy every cell of `mat` that differs from `background`.
+    pub fn from_mat<B>(mat: &Grid<T, B>, background: T) -> Self
+    where
+        T: Clone,
+    {
+        let mut grid = Self::new(background);
+        for (i, row) in mat.rows().enumerate() {
+            for (j, el) in row.iter().enumerate() {
+                grid.set((i as isize, j as isize), el.clone());
+            }
+        }
+        grid
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    pub fn get(&self, ix: SignedIx) -> &T {
+        self.cells.get(&ix).unwrap_or(&self.background)
+    }
+
+    pub fn set(&mut self, (i, j): SignedIx, el: T) {
+        if el == self.background {
+            self.cells.remove(&(i, j));
+            return;
+        }
+        self.bounds = Some(match self.bounds {
+            None => ((i, j), (i, j)),
+            Some(((li, lj), (hi, hj))) => ((li.min(i), lj.min(j)), (hi.max(i), hj.max(j))),
+        });
+        self.cells.insert((i, j), el);
+    }
+
+    /// Number of cells that differ from the background.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Cells that differ from the background, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (SignedIx, &T)> {
+        self.cells.iter().map(|(ix, el)| (*ix, el))
+    }
+
+    /// Inclusive corners of a box containing every cell that was ever set to
+    /// something other than the background. Resetting cells does not shrink it.
+    pub fn bounds(&self) -> Option<(SignedIx, SignedIx)> {
+        self.bounds
+    }
+
+    /// The cells `lo..=hi` as a dense grid.
+    pub fn window(&self, (li, lj): SignedIx, (hi, hj): SignedIx) -> Mat<T>
+    where
+        T: Clone,
+    {
+        let (h, w) = ((hi - li + 1).max(0), (hj - lj + 1).max(0));
+        Mat::from_fn(h as usize, w as usize, |(i, j)| {
+            self.get((li + i as isize, lj + j as isize)).clone()
+        })
+    }
+
+    /// The cells within `bounds` as a dense grid.
+    pub fn to_mat(&self) -> Mat<T>
+    where
+        T: Clone,
+    {
+        match self.bounds {
+            Some((lo, hi)) => self.window(lo, hi),
+            None => Mat::from_vec(0, 0, Vec::new()),
+        }
+    }
+}
+
+impl<T: PartialEq> Extend<(SignedIx, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (SignedIx, T)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(ix, el)| self.set(ix, el));
+    }
+}
+
+/// Collects onto the `Default` background.
+impl<T: PartialEq + Default> FromIterator<(SignedIx, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (SignedIx, T)>>(iter: I) -> Self {
+        let mut grid = Self::new(T::default());
+        grid.extend(iter);
+        grid
+    }
+}
+
+/// Draws the cells within `bounds` like `Mat` does.
+impl<T: PartialEq + Clone + std::fmt::Display> std::fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_mat().fmt(f)
+    }
+}
+
+/// Boolean grid with 64 cells per word. Column `j` of a row is bit `j % 64` of
+/// word `j / 64`; bits past the last column are always zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    height: usize,
+    width: usize,
+    row_words: usize,
+}
+
+impl BitGrid {
+    pub fn new(height: usize, width: usize) -> Self {
+        let row_words = width.div_ceil(64);
+        Self {
+            words: vec![0; height * row_words],
+            height,
+            width,
+            row_words,
+        }
+    }
+
+    pub fn from_fn(height: usize, width: usize, mut f: impl FnMut(Ix) -> bool) -> Self {
+        let mut grid = Self::new(height, width);
+        for i in 0..height {
+            for j in 0..width {
+                if f((i, j)) {
+                    grid.set((i, j), true);
+                }
+            }
+        }
+        grid
+    }
+
+    /// The cells of `grid` for which `f` holds.
+    pub fn from_grid<T, B>(grid: &Grid<T, B>, f: impl Fn(&T) -> bool) -> Self {
+        Self::from_fn(grid.height, grid.width, |ix| f(&grid[ix]))
+    }
+
+    pub fn to_mat(&self) -> Mat<bool> {
+        Mat::from_fn(self.height, self.width, |ix| self.get(ix))
+    }
+
+    fn word_bit(&self, (i, j): Ix) -> (usize, u64) {
+        assert!(
+            i < self.height && j < self.width,
+            "index {:?} out of bounds for {}x{} grid",
+            (i, j),
+            self.height,
+            self.width
+        );
+        (i * self.row_words + j / 64, 1 << (j % 64))
+    }
+
+    pub fn set(&mut self, ix: Ix, bit: bool) {
+        let (w, mask) = self.word_bit(ix);
+        if bit {
+            self.words[w] |= mask;
+        } else {
+            self.words[w] &= !mask;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_words(&self, i: usize) -> &[u64] {
+        &self.words[i * self.row_words..(i + 1) * self.row_words]
+    }
+
+    /// Mask of the valid bits in the last word of a row.
+    fn last_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => !0,
+            r => (1 << r) - 1,
+        }
+    }
+
+    fn map_rows(&self, mut f: impl FnMut(usize, &mut [u64])) -> Self {
+        let mut out = self.clone();
+        let mask = self.last_mask();
+        if self.row_words > 0 {
+            for (i, row) in out.words.chunks_mut(self.row_words).enumerate() {
+                f(i, row);
+                *row.last_mut().unwrap() &= mask;
+            }
+        }
+        out
+    }
+
+    /// Every cell moved `d` columns east, or west for negative `d`. Cells
+    /// pushed over an edge reappear at the other one if `wrap` is set.
+    pub fn shift_cols(&self, d: isize, wrap: bool) -> Self {
+        let width = self.width;
+        let d = if wrap {
+            d.rem_euclid(width.max(1) as isize)
+        } else {
+            d.clamp(-(width as isize), width as isize)
+        };
+        let s = d.unsigned_abs();
+        self.map_rows(|i, row| {
+            let old = self.row_words(i);
+            if d >= 0 {
+                shl_words(old, s, row);
+                if wrap && s > 0 {
+                    or_shr_words(old, width - s, row);
+                }
+            } else {
+                // Wrapping shifts are all turned into eastward ones above.
+                shr_words(old, s, row);
+            }
+        })
+    }
+
+    /// Every cell moved `d` rows south, or north for negative `d`.
+    pub fn shift_rows(&self, d: isize, wrap: bool) -> Self {
+        let h = self.height as isize;
+        self.map_rows(|i, row| {
+            let src = i as isize - d;
+            let src = if wrap {
+                Some(src.rem_euclid(h.max(1)))
+            } else {
+                (0..h).contains(&src).then_some(src)
+            };
+            match src {
+                Some(src) => row.copy_from_slice(self.row_words(src as usize)),
+                None => row.fill(0),
+            }
+        })
+    }
+
+    /// The 3x3 block centered on `(i, j)` as a 9-bit number, read row by row
+    /// with the top left cell as the highest bit. Cells off the grid count as
+    /// `outside`.
+    pub fn window9(&self, (i, j): SignedIx, outside: bool) -> usize {
+        let mut n = 0;
+        for r in i - 1..=i + 1 {
+            let bits = if (0..self.height as isize).contains(&r) {
+                self.row_bits(r as usize, j - 1, 3, outside)
+            } else if outside {
+                0b111
+            } else {
+                0
+            };
+            // Column `j - 1` is the lowest bit of a row but the highest here.
+            let bits = (bits & 1) << 2 | (bits & 2) | bits >> 2;
+            n = n << 3 | bits as usize;
+        }
+        n
+    }
+
+    /// Columns `lo..lo + n` of row `i` as an `n`-bit number with column `lo`
+    /// as the lowest bit, read from at most two words. Columns off the grid
+    /// count as `outside`.
+    fn row_bits(&self, i: usize, lo: isize, n: usize, outside: bool) -> u64 {
+        debug_assert!(n < 64);
+        let all = (1 << n) - 1;
+        let start = lo.clamp(0, self.width as isize) as usize;
+        let end = (lo + n as isize).clamp(0, self.width as isize) as usize;
+        if start >= end {
+            return if outside { all } else { 0 };
+        }
+        let words = self.row_words(i);
+        let (k, b) = (start / 64, start % 64);
+        let mut w = words[k] >> b;
+        if b > 0 && k + 1 < words.len() {
+            w |= words[k + 1] << (64 - b);
+        }
+        let skip = (start as isize - lo) as usize;
+        let inside = ((1 << (end - start)) - 1) << skip;
+        let bits = (w << skip) & inside;
+        if outside {
+            bits | (all & !inside)
+        } else {
+            bits
+        }
+    }
+}
+
+/// `out = words << s`, as one number with the lowest bits in `words[0]`.
+fn shl_words(words: &[u64], s: usize, out: &mut [u64]) {
+    out.fill(0);
+    or_shl_words(words, s, out);
+}
+
+fn shr_words(words: &[u64], s: usize, out: &mut [u64]) {
+    out.fill(0);
+    or_shr_words(words, s, out);
+}
+
+fn or_shl_words(words: &[u64], s: usize, out: &mut [u64]) {
+    let (ws, bs) = (s / 64, s % 64);
+    for k in ws..out.len() {
+        out[k] |= words[k - ws] << bs;
+        if bs > 0 && k > ws {
+            out[k] |= words[k - ws - 1] >> (64 - bs);
+        }
+    }
+}
+
+fn or_shr_words(words: &[u64], s: usize, out: &mut [u64]) {
+    let (ws, bs) = (s / 64, s % 64);
+    for k in 0..out.len().saturating_sub(ws) {
+        out[k] |= words[k + ws] >> bs;
+        if bs > 0 && k + ws + 1 < words.len() {
+            out[k] |= words[k + ws + 1] << (64 - bs);
+        }
+    }
+}
+
+impl<'a> MatGet<'a, bool> for BitGrid {
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn get(&self, ix: Ix) -> bool {
+        let (w, mask) = self.word_bit(ix);
+        self.words[w] & mask != 0
+    }
+}
+
+macro_rules! bit_grid_op {
+    ( $( $op:ident::$f:ident ),* ) => {
+        $(
+            impl std::ops::$op for &BitGrid {
+                type Output = BitGrid;
+
+                fn $f(self, other: &BitGrid) -> BitGrid {
+                    assert_eq!(
+                        (self.height, self.width),
+                        (other.height, other.width),
+                        "grids differ in size"
+                    );
+                    let mut out = self.clone();
+                    out.words
+                        .iter_mut()
+                        .zip(&other.words)
+                        .for_each(|(a, b)| *a = std::ops::$op::$f(*a, *b));
+                    out
                 }
-            })
-            .collect_vec();
-        let (m, n) = (mat.len(), mat[0].len());
-        Self { mat, m, n }
+            }
+        )*
+    };
+}
+
+bit_grid_op!(BitAnd::bitand, BitOr::bitor, BitXor::bitxor);
+
+impl std::ops::Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        self.map_rows(|_, row| row.iter_mut().for_each(|w| *w = !*w))
     }
 }
 
-impl<T: std::fmt::Display> std::fmt::Display for Torus<T> {
+/// Rows of `#` and `.`, like a `Mat<bool>`.
+impl std::fmt::Display for BitGrid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in 0..self.m {
-            for j in 0..self.n {
-                write!(f, "{}", self.mat[i][j])?
+        self.to_mat().render().fmt(f)
+    }
+}
+
+// Grids with one element per character, see the line types in `input`.
+
+fn char_grid<T>(s: &str, parse_line: impl Fn(&str) -> Result<Vec<T>, CharError>) -> Vec<Vec<T>> {
+    s.lines()
+        .enumerate()
+        .map(|(row, line)| {
+            parse_line(line)
+                .map_err(|err| GridCharError { row, err })
+                .unwrap_or_else(|e| panic!("{}", e))
+        })
+        .collect_vec()
+}
+
+macro_rules! char_grid_input {
+    ( $( $wrap:ident<$el:ty> $(where $t:ident: $bound:path)? ),* ) => {
+        $(
+            impl<$($t: $bound,)? B: Default> AoCInput for $wrap<Grid<$el, B>> {
+                fn from_input(s: &str) -> Self {
+                    let rows = char_grid(s, |line| $wrap::<Vec<$el>>::try_from_line(line).map(|l| l.0));
+                    $wrap(Grid::try_from_rows(rows).unwrap_or_else(|e| panic!("{}", e)))
+                }
+            }
+        )*
+    };
+}
+
+char_grid_input!(
+    Digits<T> where T: From<u8>,
+    HexDigits<T> where T: From<u8>,
+    Bits<bool>,
+    Chars<char>
+);
+
+#[cfg(test)]
+mod test {
+    use super::{BitGrid, Mat, MatGet, RaggedRows, SparseGrid, Torus};
+    use crate::boundary::{Clamped, Constant, Reflecting};
+    use crate::input::{AoCInput, Digits};
+    use itertools::Itertools;
+
+    #[test]
+    fn test_flat_storage() {
+        let Digits(mut mat) = Digits::<Mat<u8>>::from_input("123\n456\n");
+        assert_eq!((mat.height(), mat.width()), (2, 3));
+        assert_eq!(mat[(1, 0)], 4);
+        assert_eq!(mat.row(1), &[4, 5, 6]);
+        mat[(0, 2)] = 9;
+        assert_eq!(mat.as_slice(), &[1, 2, 9, 4, 5, 6]);
+        assert_eq!(mat.rows().count(), 2);
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let err = Mat::try_from_rows(vec![vec![1, 2], vec![3]]).unwrap_err();
+        assert_eq!(
+            err,
+            RaggedRows {
+                row: 1,
+                len: 1,
+                expected: 2
+            }
+        );
+        let mat = Mat::<i32>::from_input("1 2\n3 4");
+        assert_eq!(mat.get((1, 1)), 4);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let mat = Mat::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(
+            mat.transpose(),
+            Mat::from_rows(vec![vec![1, 4], vec![2, 5], vec![3, 6]])
+        );
+        assert_eq!(
+            mat.rotate_cw(),
+            Mat::from_rows(vec![vec![4, 1], vec![5, 2], vec![6, 3]])
+        );
+        assert_eq!(
+            mat.rotate_ccw(),
+            Mat::from_rows(vec![vec![3, 6], vec![2, 5], vec![1, 4]])
+        );
+        assert_eq!(mat.rotate_cw().rotate_cw(), mat.flip_h().flip_v());
+        assert_eq!(
+            mat.sub_mat((0, 1), 2, 2),
+            Mat::from_rows(vec![vec![2, 3], vec![5, 6]])
+        );
+        assert_eq!(mat.col(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(
+            mat.map(|x| x % 2 == 0),
+            Mat::from_fn(2, 3, |(i, j)| (i + j) % 2 == 1)
+        );
+        assert_eq!(Mat::new(2, 3, 0).as_slice(), &[0; 6]);
+    }
+
+    #[test]
+    fn test_checked_indexing() {
+        let Digits(mat) = Digits::<Mat<u8>>::from_input("123\n456\n");
+        assert_eq!(mat.get_checked((1, 2)), Some(6));
+        assert_eq!(mat.get_checked((2, 0)), None);
+        assert_eq!(mat.get_signed((-1, 0)), None);
+        assert_eq!(mat.offset((1, 1), (-1, 1)), Some((0, 2)));
+        assert_eq!(mat.offset((1, 1), (0, 2)), None);
+        assert_eq!(
+            mat.iter_bordering_idx((0, 0)).collect_vec(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(mat.iter_diag_bordering_idx((1, 2)).count(), 3);
+
+        let Digits(tor) = Digits::<Torus<u8>>::from_input("123\n456\n");
+        assert_eq!(tor.get_signed((-1, -1)), Some(6));
+        assert_eq!(tor.offset((0, 0), (-1, 4)), Some((1, 1)));
+        assert_eq!(
+            tor.iter_bordering_idx((0, 0)).collect_vec(),
+            vec![(1, 0), (0, 1), (1, 0), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_boundary_policies() {
+        let Digits(mat) = Digits::<Mat<u8>>::from_input("123\n456\n");
+        let clamped = mat.clone().with_boundary(Clamped);
+        assert_eq!(clamped.get_signed((-5, 7)), Some(3));
+        assert_eq!(clamped.iter_bordering_idx((0, 0)).count(), 2);
+        assert!(clamped.iter_diag_bordering_idx((0, 0)).all(|n| n != (0, 0)));
+        let reflecting = mat.clone().with_boundary(Reflecting);
+        assert_eq!(reflecting.get_signed((-1, 3)), Some(3));
+        assert_eq!(
+            reflecting.iter_bordering_idx((1, 2)).collect::<Vec<_>>(),
+            vec![(0, 2), (1, 1)]
+        );
+        let constant = mat.with_boundary(Constant(0));
+        assert_eq!(constant.get_signed((2, 0)), Some(0));
+        assert_eq!(constant.get_signed((1, 0)), Some(4));
+        assert_eq!(constant.iter_bordering_idx((0, 0)).count(), 2);
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let mat = Mat::<bool>::from_input("#..#\n.##.\n");
+        let grid = BitGrid::from_grid(&mat, |b| *b);
+        assert_eq!(grid.to_string(), "#..#\n.##.\n");
+        assert_eq!(grid.count_ones(), 4);
+        assert_eq!(grid.shift_cols(1, false).to_string(), ".#..\n..##\n");
+        assert_eq!(grid.shift_cols(1, true).to_string(), "##..\n..##\n");
+        assert_eq!(grid.shift_cols(-1, true).to_string(), "..##\n##..\n");
+        assert_eq!(grid.shift_rows(1, false).to_string(), "....\n#..#\n");
+        assert_eq!((!&grid).to_string(), ".##.\n#..#\n");
+        assert_eq!((&grid & &grid.shift_rows(1, true)).count_ones(), 0);
+        assert_eq!(
+            (&grid ^ &grid.shift_cols(-3, false)).to_string(),
+            "...#\n.##.\n"
+        );
+        assert_eq!(grid.window9((0, 1), false), 0b000_100_011);
+        assert_eq!(grid.window9((0, 0), true), 0b111_110_101);
+    }
+
+    #[test]
+    fn test_bit_grid_wide_shifts() {
+        let grid = BitGrid::from_fn(2, 130, |(i, j)| (i + j) % 7 == 0);
+        for d in [-129, -64, -63, -1, 1, 63, 64, 65, 129] {
+            let shifted = grid.shift_cols(d, true);
+            assert!(grid.iter_idx().all(|(i, j)| {
+                let to = (j as isize + d).rem_euclid(130) as usize;
+                shifted.get((i, to)) == grid.get((i, j))
+            }));
+            let shifted = grid.shift_cols(d, false);
+            assert_eq!(
+                shifted.count_ones(),
+                grid.iter_idx()
+                    .filter(|&(i, j)| grid.get((i, j)) && (0..130).contains(&(j as isize + d)))
+                    .count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_bit_grid_wide_window9() {
+        let grid = BitGrid::from_fn(3, 130, |(i, j)| (i * 5 + j) % 3 == 0);
+        for outside in [false, true] {
+            for (i, j) in [
+                (0, 0),
+                (1, 63),
+                (1, 64),
+                (2, 65),
+                (1, 128),
+                (2, 129),
+                (-1, 130),
+            ] {
+                let mut want = 0;
+                for di in -1..=1 {
+                    for dj in -1..=1 {
+                        let bit = grid.get_signed((i + di, j + dj)).unwrap_or(outside);
+                        want = want << 1 | bit as usize;
+                    }
+                }
+                assert_eq!(grid.window9((i, j), outside), want, "{:?}", (i, j));
             }
-            writeln!(f)?
         }
-        Ok(())
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid = SparseGrid::new('.');
+        grid.set((-1, 2), '#');
+        grid.set((1, -1), '#');
+        grid.set((0, 0), '.');
+        assert_eq!(grid.len(), 2);
+        assert_eq!(*grid.get((5, 5)), '.');
+        assert_eq!(grid.bounds(), Some(((-1, -1), (1, 2))));
+        assert_eq!(grid.to_string(), "...#\n....\n#...\n");
+
+        let mat = grid.to_mat();
+        let back = SparseGrid::from_mat(&mat, '.');
+        assert_eq!(back.iter().count(), 2);
+        assert_eq!(*back.get((0, 3)), '#');
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_column_out_of_bounds() {
+        let mat = Mat::from_vec(2, 2, vec![1, 2, 3, 4]);
+        let _ = mat[(0, 2)];
     }
 }
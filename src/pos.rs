@@ -1,10 +1,12 @@
 use std::{
     fmt::Display,
     iter::Sum,
-    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+    ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
-use crate::input::AoCLineInput;
+use itertools::Itertools;
+
+use crate::{input::AoCLineInput, matrix::Ix};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Pos<T> {
@@ -64,6 +66,19 @@ impl<T: std::hash::Hash> std::hash::Hash for Pos<T> {
     }
 }
 
+// Reading order: top to bottom, then left to right.
+impl<T: Ord> PartialOrd for Pos<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Pos<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.y.cmp(&other.y).then_with(|| self.x.cmp(&other.x))
+    }
+}
+
 impl<T: Ord + Copy> Pos<T> {
     pub fn elementwise_clamp(&self, min: T, max: T) -> Self {
         Self {
@@ -73,6 +88,72 @@ impl<T: Ord + Copy> Pos<T> {
     }
 }
 
+impl<T: Copy + Add<Output = T> + From<i8>> Pos<T> {
+    pub fn step(&self, dir: impl Into<Dir8>) -> Self {
+        *self + dir.into().delta()
+    }
+
+    pub fn neighbours4(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        Dir4::ALL.into_iter().map(move |d| p.step(d))
+    }
+
+    pub fn neighbours8(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        Dir8::ALL.into_iter().map(move |d| p.step(d))
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Pos<T> {
+    pub fn scale(&self, k: T) -> Self {
+        Self {
+            x: self.x * k,
+            y: self.y * k,
+        }
+    }
+}
+
+impl<T: Coord> Pos<T> {
+    pub fn manhattan(&self, other: &Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Rotate by 90° around the origin, clockwise on screen (y pointing down).
+    pub fn rotate_cw(&self) -> Self {
+        Self {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        Self {
+            x: self.y,
+            y: -self.x,
+        }
+    }
+}
+
+// Matrix indices are (row, column), i.e. (y, x).
+impl<T: Copy + TryFrom<usize> + TryInto<usize>> Pos<T> {
+    pub fn from_ix((i, j): Ix) -> Option<Self> {
+        Some(Self {
+            x: j.try_into().ok()?,
+            y: i.try_into().ok()?,
+        })
+    }
+
+    pub fn to_ix(&self) -> Option<Ix> {
+        Some((self.y.try_into().ok()?, self.x.try_into().ok()?))
+    }
+}
+
+impl<T> From<(T, T)> for Pos<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Pos { x, y }
+    }
+}
+
 impl<T: Default> Default for Pos<T> {
     fn default() -> Self {
         Pos::zero()
@@ -151,3 +232,476 @@ where
         Pos { x, y }
     }
 }
+
+/// Direction on screen, with north pointing to lower y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir4 {
+    /// Clockwise, starting north.
+    pub const ALL: [Dir4; 4] = [Dir4::North, Dir4::East, Dir4::South, Dir4::West];
+
+    fn from_index(i: usize) -> Self {
+        Self::ALL[i % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::from_index(self as usize + 1)
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::from_index(self as usize + 3)
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::from_index(self as usize + 2)
+    }
+
+    pub fn delta<T: From<i8>>(self) -> Pos<T> {
+        Dir8::from(self).delta()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    /// Clockwise, starting north.
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    fn from_index(i: usize) -> Self {
+        Self::ALL[i % 8]
+    }
+
+    /// Turn by 45° clockwise.
+    pub fn turn_right(self) -> Self {
+        Self::from_index(self as usize + 1)
+    }
+
+    /// Turn by 45° counter-clockwise.
+    pub fn turn_left(self) -> Self {
+        Self::from_index(self as usize + 7)
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::from_index(self as usize + 4)
+    }
+
+    pub fn delta<T: From<i8>>(self) -> Pos<T> {
+        let (x, y) = match self {
+            Dir8::North => (0, -1),
+            Dir8::NorthEast => (1, -1),
+            Dir8::East => (1, 0),
+            Dir8::SouthEast => (1, 1),
+            Dir8::South => (0, 1),
+            Dir8::SouthWest => (-1, 1),
+            Dir8::West => (-1, 0),
+            Dir8::NorthWest => (-1, -1),
+        };
+        Pos {
+            x: T::from(x),
+            y: T::from(y),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(d: Dir4) -> Self {
+        Self::from_index(2 * d as usize)
+    }
+}
+
+/// Signed integers that can be used as `Vector` coordinates.
+pub trait Coord:
+    Copy
+    + Default
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ( $($t:ty),* ) => {
+        $(
+            impl Coord for $t {
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, i128, isize);
+
+/// N-dimensional integer vector, ordered lexicographically by component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vector<T, const N: usize>(pub [T; N]);
+
+pub type V2<T> = Vector<T, 2>;
+pub type V3<T> = Vector<T, 3>;
+
+impl<T: Copy, const N: usize> Vector<T, N> {
+    pub fn new(coords: [T; N]) -> Self {
+        Self(coords)
+    }
+
+    pub fn splat(t: T) -> Self {
+        Self([t; N])
+    }
+
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Vector<U, N> {
+        Vector(self.0.map(f))
+    }
+
+    pub fn zip_with<U: Copy, V>(
+        &self,
+        other: &Vector<U, N>,
+        f: impl Fn(T, U) -> V,
+    ) -> Vector<V, N> {
+        Vector(std::array::from_fn(|i| f(self.0[i], other.0[i])))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Vector<T, N> {
+    pub fn zero() -> Self {
+        Self::splat(T::default())
+    }
+}
+
+/// Component-wise counterparts of `min`, `max` and `clamp`, which `Vector`
+/// gets from `Ord` with their lexicographic meaning.
+impl<T: Copy + Ord, const N: usize> Vector<T, N> {
+    pub fn component_min(&self, other: &Self) -> Self {
+        self.zip_with(other, std::cmp::min)
+    }
+
+    pub fn component_max(&self, other: &Self) -> Self {
+        self.zip_with(other, std::cmp::max)
+    }
+
+    pub fn component_clamp(&self, min: T, max: T) -> Self {
+        self.map(|t| t.clamp(min, max))
+    }
+}
+
+impl<T: Coord, const N: usize> Vector<T, N> {
+    pub fn abs(&self) -> Self {
+        self.map(T::abs)
+    }
+
+    pub fn signum(&self) -> Self {
+        self.map(T::signum)
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.zip_with(other, |a, b| a * b)
+            .iter()
+            .fold(T::default(), |acc, t| acc + t)
+    }
+
+    /// Sum of absolute components (L1 norm).
+    pub fn manhattan(&self) -> T {
+        self.abs().iter().fold(T::default(), |acc, t| acc + t)
+    }
+
+    /// Largest absolute component (L∞ norm).
+    pub fn chebyshev(&self) -> T {
+        self.abs().iter().fold(T::default(), std::cmp::max)
+    }
+}
+
+impl<T: Copy> Vector<T, 2> {
+    pub fn x(&self) -> T {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> T {
+        self.0[1]
+    }
+}
+
+impl<T: Copy> Vector<T, 3> {
+    pub fn x(&self) -> T {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> T {
+        self.0[1]
+    }
+
+    pub fn z(&self) -> T {
+        self.0[2]
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for Vector<T, N> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<T, const N: usize> Index<usize> for Vector<T, N> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        &self.0[i]
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for Vector<T, N> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        &mut self.0[i]
+    }
+}
+
+impl<T: Display, const N: usize> Display for Vector<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join(","))
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T, N> {
+    fn from(coords: [T; N]) -> Self {
+        Self(coords)
+    }
+}
+
+impl<T> From<Pos<T>> for Vector<T, 2> {
+    fn from(p: Pos<T>) -> Self {
+        Self([p.x, p.y])
+    }
+}
+
+impl<T> From<Vector<T, 2>> for Pos<T> {
+    fn from(Vector([x, y]): Vector<T, 2>) -> Self {
+        Pos { x, y }
+    }
+}
+
+impl<T> From<(T, T, T)> for Vector<T, 3> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self([x, y, z])
+    }
+}
+
+impl<T: Copy + Add<Output = T>, const N: usize> Add for Vector<T, N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.zip_with(&rhs, |a, b| a + b)
+    }
+}
+
+impl<T: Copy + AddAssign, const N: usize> AddAssign for Vector<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (t, r) in self.0.iter_mut().zip(rhs.0) {
+            *t += r;
+        }
+    }
+}
+
+impl<T: Copy + Sub<Output = T>, const N: usize> Sub for Vector<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.zip_with(&rhs, |a, b| a - b)
+    }
+}
+
+impl<T: Copy + SubAssign, const N: usize> SubAssign for Vector<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (t, r) in self.0.iter_mut().zip(rhs.0) {
+            *t -= r;
+        }
+    }
+}
+
+impl<T: Copy + Neg<Output = T>, const N: usize> Neg for Vector<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|t| -t)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>, const N: usize> Mul<T> for Vector<T, N> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        self.map(|t| t * rhs)
+    }
+}
+
+impl<T: Copy + MulAssign, const N: usize> MulAssign<T> for Vector<T, N> {
+    fn mul_assign(&mut self, rhs: T) {
+        for t in self.0.iter_mut() {
+            *t *= rhs;
+        }
+    }
+}
+
+impl<T: Copy + Default + AddAssign, const N: usize> Sum for Vector<T, N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut tot = Self::zero();
+        for t in iter {
+            tot += t;
+        }
+        tot
+    }
+}
+
+impl<T, const N: usize> AoCLineInput for Vector<T, N>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Debug,
+{
+    fn from_line(s: &str) -> Self {
+        let coords = s
+            .split(',')
+            .map(|c| c.trim().parse().unwrap())
+            .collect_vec();
+        let len = coords.len();
+        match coords.try_into() {
+            Ok(coords) => Self(coords),
+            Err(_) => panic!("expected {} coordinates, got {} in {:?}", N, len, s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Dir4, Dir8, Pos, Vector, V2, V3};
+
+    #[test]
+    fn test_dir_rotation() {
+        for d in Dir4::ALL {
+            assert_eq!(d.turn_right().turn_left(), d);
+            assert_eq!(d.turn_right().turn_right(), d.opposite());
+            assert_eq!(
+                Dir8::from(d).turn_right().turn_right(),
+                d.turn_right().into()
+            );
+        }
+        assert_eq!(Dir4::West.turn_right(), Dir4::North);
+        assert_eq!(Dir4::North.turn_left(), Dir4::West);
+        assert_eq!(Dir8::NorthWest.turn_right(), Dir8::North);
+        assert_eq!(Dir8::North.turn_left(), Dir8::NorthWest);
+        assert_eq!(Dir8::SouthEast.opposite(), Dir8::NorthWest);
+    }
+
+    #[test]
+    fn test_dir_offsets() {
+        assert_eq!(Dir4::North.delta::<i32>(), Pos::new(0, -1));
+        assert_eq!(Dir4::East.delta::<i32>(), Pos::new(1, 0));
+        assert_eq!(Dir8::SouthWest.delta::<i32>(), Pos::new(-1, 1));
+        for d in Dir8::ALL {
+            assert_eq!(d.delta::<i32>() + d.opposite().delta(), Pos::zero());
+        }
+        // Turning the direction right turns its offset clockwise.
+        for d in Dir4::ALL {
+            assert_eq!(d.delta::<i32>().rotate_cw(), d.turn_right().delta());
+            assert_eq!(d.delta::<i32>().rotate_ccw(), d.turn_left().delta());
+        }
+    }
+
+    #[test]
+    fn test_dir_all() {
+        assert_eq!(Dir4::ALL.len(), 4);
+        assert_eq!(Dir8::ALL.len(), 8);
+        let (dir4, dir8): (Vec<Pos<i32>>, Vec<Pos<i32>>) = (
+            Dir4::ALL.iter().map(|d| d.delta()).collect(),
+            Dir8::ALL.iter().map(|d| d.delta()).collect(),
+        );
+        assert!(dir4.iter().all(|p| p.manhattan(&Pos::zero()) == 1));
+        assert!(dir4.iter().all(|p| dir8.contains(p)));
+        for (i, p) in dir8.iter().enumerate() {
+            assert!(!dir8[i + 1..].contains(p));
+            assert!(p.x.abs() <= 1 && p.y.abs() <= 1 && *p != Pos::zero());
+        }
+    }
+
+    #[test]
+    fn test_pos_helpers() {
+        let p = Pos::new(3, -2);
+        assert_eq!(p.neighbours4().count(), 4);
+        assert_eq!(p.neighbours4().next(), Some(Pos::new(3, -3)));
+        assert_eq!(p.neighbours8().filter(|q| q.manhattan(&p) == 2).count(), 4);
+        assert_eq!(p.step(Dir8::NorthEast), Pos::new(4, -3));
+        assert_eq!(p.scale(2), Pos::new(6, -4));
+        assert_eq!(p.elementwise_clamp(-1, 1), Pos::new(1, -1));
+        assert_eq!(p.to_ix(), None);
+        assert_eq!(Pos::<i32>::from_ix((5, 7)), Some(Pos::new(7, 5)));
+        assert_eq!(Pos::new(7, 5).to_ix(), Some((5, 7)));
+        // Reading order: by row first.
+        assert!(Pos::new(9, 0) < Pos::new(0, 1));
+    }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let a = V3::new([1, -2, 3]);
+        let b = V3::new([4, 5, -6]);
+        assert_eq!(a + b, V3::new([5, 3, -3]));
+        assert_eq!(a - b, V3::new([-3, -7, 9]));
+        assert_eq!(-a, V3::new([-1, 2, -3]));
+        assert_eq!(a * 2, V3::new([2, -4, 6]));
+        assert_eq!(a.dot(&b), 4 - 10 - 18);
+        assert_eq!(a.manhattan(), 6);
+        assert_eq!(b.chebyshev(), 6);
+        assert_eq!([a, b, a].into_iter().sum::<V3<i32>>(), V3::new([6, 1, 0]));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        c *= -1;
+        assert_eq!(c, -b);
+    }
+
+    #[test]
+    fn test_vector_component_min_max() {
+        let a = V2::new([1, 9]);
+        let b = V2::new([2, 0]);
+        // `Ord` compares lexicographically.
+        assert_eq!(a.min(b), a);
+        assert_eq!(a.component_min(&b), V2::new([1, 0]));
+        assert_eq!(a.component_max(&b), V2::new([2, 9]));
+        assert_eq!(a.component_clamp(2, 5), V2::new([2, 5]));
+        assert_eq!(Vector::splat(7).component_clamp(0, 3), V2::new([3, 3]));
+    }
+}
@@ -0,0 +1,155 @@
+// Drawing grids as text.
+use std::{collections::HashSet, fmt::Display};
+
+use itertools::Itertools;
+
+use crate::matrix::{Grid, Ix};
+
+/// Elements with a canonical one-character form.
+pub trait ToChar {
+    fn to_char(&self) -> char;
+}
+
+impl ToChar for char {
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+impl ToChar for bool {
+    fn to_char(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+impl ToChar for u8 {
+    fn to_char(&self) -> char {
+        char::from_digit(*self as u32, 36)
+            .unwrap_or_else(|| panic!("{} has no single digit form", self))
+    }
+}
+
+/// Draws every element as the character returned by `cell`. Cells covered by
+/// a highlight are drawn with its character instead; later highlights win.
+pub struct Render<'a, T, F, B> {
+    mat: &'a Grid<T, B>,
+    cell: F,
+    highlights: Vec<(HashSet<Ix>, char)>,
+}
+
+impl<'a, T, F: Fn(&T) -> char, B> Render<'a, T, F, B> {
+    pub fn highlight(mut self, cells: impl IntoIterator<Item = Ix>, c: char) -> Self {
+        self.highlights.push((cells.into_iter().collect(), c));
+        self
+    }
+
+    fn char_at(&self, ix: Ix) -> char {
+        self.highlights
+            .iter()
+            .rev()
+            .find(|(cells, _)| cells.contains(&ix))
+            .map_or_else(|| (self.cell)(&self.mat[ix]), |(_, c)| *c)
+    }
+}
+
+impl<'a, T, F: Fn(&T) -> char, B> Display for Render<'a, T, F, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.mat.rows().enumerate() {
+            for j in 0..row.len() {
+                write!(f, "{}", self.char_at((i, j)))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes every row on its own line with `sep` between the elements.
+pub struct Separated<'a, T, B> {
+    mat: &'a Grid<T, B>,
+    sep: &'a str,
+}
+
+impl<'a, T: Display, B> Display for Separated<'a, T, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.mat.rows() {
+            writeln!(f, "{}", row.iter().format(self.sep))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, B> Grid<T, B> {
+    /// The elements with `sep` between them, e.g. `" "` so that numbers with
+    /// several digits parse again with `Mat::from_input`.
+    pub fn separated<'a>(&'a self, sep: &'a str) -> Separated<'a, T, B> {
+        Separated { mat: self, sep }
+    }
+
+    pub fn render_with<F: Fn(&T) -> char>(&self, cell: F) -> Render<'_, T, F, B> {
+        Render {
+            mat: self,
+            cell,
+            highlights: Vec::new(),
+        }
+    }
+
+    pub fn render(&self) -> Render<'_, T, fn(&T) -> char, B>
+    where
+        T: ToChar,
+    {
+        self.render_with(T::to_char)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::{AoCInput, Chars, Digits};
+    use crate::matrix::Mat;
+
+    #[test]
+    fn test_render_highlight() {
+        let Digits(mat) = Digits::<Mat<u8>>::from_input("123\n456\n");
+        assert_eq!(mat.render().to_string(), "123\n456\n");
+        let out = mat
+            .render_with(|d| if d % 2 == 0 { '#' } else { '.' })
+            .highlight([(0, 0), (1, 1)], 'o')
+            .highlight([(1, 1)], '*')
+            .to_string();
+        assert_eq!(out, "o#.\n#*#\n");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let s = "#.#\n..#\n";
+        let Chars(mat) = Chars::<Mat<char>>::from_input(s);
+        assert_eq!(mat.render().to_string(), s);
+        assert_eq!(mat.to_string(), s);
+        assert_eq!(Chars::<Mat<char>>::from_input(&mat.to_string()).0, mat);
+
+        let Digits(mat) = Digits::<Mat<u8>>::from_input("123\n456\n");
+        assert_eq!(mat.to_string(), "123\n456\n");
+
+        let mat = Mat::<i32>::from_input("10 -2\n3 400\n");
+        assert_eq!(mat.separated(" ").to_string(), "10 -2\n3 400\n");
+        assert_eq!(Mat::<i32>::from_input(&mat.separated(" ").to_string()), mat);
+
+        let mat = Mat::<u32>::from_rows(vec![vec![10, 2], vec![0, 345]]);
+        assert_eq!(mat.to_string(), "102\n0345\n");
+        assert_eq!(mat.separated(", ").to_string(), "10, 2\n0, 345\n");
+        assert_eq!(Mat::<u32>::from_input(&mat.separated(" ").to_string()), mat);
+    }
+
+    #[test]
+    fn test_empty_rows() {
+        let mat = Mat::new(3, 0, 0u8);
+        assert_eq!(mat.rows().count(), 3);
+        assert_eq!(mat.to_string(), "\n\n\n");
+        assert_eq!(mat.render().to_string(), "\n\n\n");
+        assert_eq!(Mat::new(0, 4, 0u8).to_string(), "");
+    }
+}
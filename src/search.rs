@@ -0,0 +1,502 @@
+// Shortest paths over implicit graphs given by a successor function.
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Add,
+};
+
+/// Path lengths: non-negative, totally ordered and zero by default.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+impl<T: Copy + Ord + Default + Add<Output = T>> Cost for T {}
+
+/// Priority queue of node indices, lowest priority first.
+pub trait Frontier<C> {
+    fn push(&mut self, priority: C, node: usize);
+    fn pop(&mut self) -> Option<(C, usize)>;
+}
+
+/// A binary heap. Ties pop the lower node index first, i.e. the node that was
+/// discovered first, however often or late it was pushed.
+#[derive(Debug, Clone)]
+pub struct HeapFrontier<C>(BinaryHeap<Reverse<(C, usize)>>);
+
+impl<C: Ord> Default for HeapFrontier<C> {
+    fn default() -> Self {
+        Self(BinaryHeap::new())
+    }
+}
+
+impl<C: Ord> Frontier<C> for HeapFrontier<C> {
+    fn push(&mut self, priority: C, node: usize) {
+        self.0.push(Reverse((priority, node)));
+    }
+
+    fn pop(&mut self) -> Option<(C, usize)> {
+        self.0.pop().map(|Reverse(entry)| entry)
+    }
+}
+
+/// Small non-negative integers usable as `BucketQueue` priorities.
+pub trait BucketKey: Copy {
+    fn to_bucket(self) -> usize;
+    fn from_bucket(bucket: usize) -> Self;
+}
+
+macro_rules! impl_bucket_key {
+    ( $($t:ty),* ) => {
+        $(
+            impl BucketKey for $t {
+                #[inline]
+                fn to_bucket(self) -> usize {
+                    self as usize
+                }
+
+                #[inline]
+                fn from_bucket(bucket: usize) -> Self {
+                    bucket as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_bucket_key!(u8, u16, u32, u64, usize);
+
+/// Dial's bucket queue: one bucket per priority, from the priority popped last
+/// up to the highest one queued. An empty queue starts at whatever is pushed
+/// next. It is monotone, so nothing may be pushed below the priority popped
+/// last. That always holds for Dijkstra and for A* with a consistent
+/// heuristic. Memory grows with the spread of queued priorities, which stays
+/// small when edge costs do.
+#[derive(Debug, Clone)]
+pub struct BucketQueue<C> {
+    buckets: VecDeque<Vec<usize>>,
+    /// The priority of `buckets[0]`.
+    base: usize,
+    /// The priority popped last.
+    floor: usize,
+    len: usize,
+    _key: PhantomData<C>,
+}
+
+impl<C> Default for BucketQueue<C> {
+    fn default() -> Self {
+        Self {
+            buckets: VecDeque::new(),
+            base: 0,
+            floor: 0,
+            len: 0,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<C: BucketKey> Frontier<C> for BucketQueue<C> {
+    fn push(&mut self, priority: C, node: usize) {
+        let bucket = priority.to_bucket();
+        assert!(
+            bucket >= self.floor,
+            "priority {} pushed after popping {}",
+            bucket,
+            self.floor
+        );
+        if self.len == 0 {
+            self.base = bucket;
+        }
+        while bucket < self.base {
+            self.buckets.push_front(Vec::new());
+            self.base -= 1;
+        }
+        let offset = bucket - self.base;
+        if offset >= self.buckets.len() {
+            self.buckets.resize_with(offset + 1, Vec::new);
+        }
+        self.buckets[offset].push(node);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(C, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.buckets[0].is_empty() {
+            // Recycle the empty bucket at the back.
+            self.buckets.rotate_left(1);
+            self.base += 1;
+        }
+        self.len -= 1;
+        self.floor = self.base;
+        let node = self.buckets[0].pop().unwrap();
+        Some((C::from_bucket(self.base), node))
+    }
+}
+
+/// Numbers the nodes reached by a search.
+pub trait NodeIndex<N> {
+    fn get(&self, node: &N) -> Option<usize>;
+    fn insert(&mut self, node: &N, ix: usize);
+}
+
+/// Works for any hashable node.
+#[derive(Debug, Clone)]
+pub struct HashIndex<N>(HashMap<N, usize>);
+
+impl<N> Default for HashIndex<N> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<N: Clone + Hash + Eq> NodeIndex<N> for HashIndex<N> {
+    fn get(&self, node: &N) -> Option<usize> {
+        self.0.get(node).copied()
+    }
+
+    fn insert(&mut self, node: &N, ix: usize) {
+        self.0.insert(node.clone(), ix);
+    }
+}
+
+/// For nodes that `key` maps into `0..len`, such as the cells of a grid. A
+/// lookup is a plain array access instead of a hash.
+#[derive(Debug, Clone)]
+pub struct DenseIndex<F> {
+    slots: Vec<usize>,
+    key: F,
+}
+
+impl<F> DenseIndex<F> {
+    pub fn new(len: usize, key: F) -> Self {
+        Self {
+            slots: vec![usize::MAX; len],
+            key,
+        }
+    }
+}
+
+impl<N, F: Fn(&N) -> usize> NodeIndex<N> for DenseIndex<F> {
+    fn get(&self, node: &N) -> Option<usize> {
+        let ix = self.slots[(self.key)(node)];
+        (ix != usize::MAX).then_some(ix)
+    }
+
+    fn insert(&mut self, node: &N, ix: usize) {
+        self.slots[(self.key)(node)] = ix;
+    }
+}
+
+/// How much work a search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Nodes taken off the frontier and expanded.
+    pub expanded: usize,
+    /// Distinct nodes reached.
+    pub discovered: usize,
+    /// Entries pushed onto the frontier, including stale ones.
+    pub pushed: usize,
+}
+
+/// A shortest path to a goal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<N, C> {
+    pub cost: C,
+    /// From the start to the goal, both included.
+    pub path: Vec<N>,
+    pub stats: Stats,
+}
+
+struct Visit<N, C> {
+    node: N,
+    parent: usize,
+    cost: C,
+    closed: bool,
+}
+
+/// The nodes reached so far with their best known cost and parent.
+struct Visited<N, C, X> {
+    visits: Vec<Visit<N, C>>,
+    index: X,
+    stats: Stats,
+}
+
+impl<N: Clone, C: Cost, X: NodeIndex<N>> Visited<N, C, X> {
+    fn new(mut index: X, start: N) -> Self {
+        index.insert(&start, 0);
+        Self {
+            visits: vec![Visit {
+                node: start.clone(),
+                parent: 0,
+                cost: C::default(),
+                closed: false,
+            }],
+            index,
+            stats: Stats {
+                discovered: 1,
+                pushed: 1,
+                ..Stats::default()
+            },
+        }
+    }
+
+    /// Records `node` as reached from `parent` at `cost`. Returns its index
+    /// unless it is known to be reachable at least as cheaply.
+    fn relax(&mut self, node: N, parent: usize, cost: C) -> Option<usize> {
+        let ix = match self.index.get(&node) {
+            Some(ix) => {
+                let visit = &mut self.visits[ix];
+                if visit.closed || visit.cost <= cost {
+                    return None;
+                }
+                visit.parent = parent;
+                visit.cost = cost;
+                ix
+            }
+            None => {
+                let ix = self.visits.len();
+                self.index.insert(&node, ix);
+                self.visits.push(Visit {
+                    node,
+                    parent,
+                    cost,
+                    closed: false,
+                });
+                self.stats.discovered += 1;
+                ix
+            }
+        };
+        self.stats.pushed += 1;
+        Some(ix)
+    }
+
+    fn found(self, goal: usize) -> Found<N, C> {
+        let cost = self.visits[goal].cost;
+        let mut path = vec![];
+        let mut ix = goal;
+        loop {
+            path.push(self.visits[ix].node.clone());
+            if ix == 0 {
+                break;
+            }
+            ix = self.visits[ix].parent;
+        }
+        path.reverse();
+        Found {
+            cost,
+            path,
+            stats: self.stats,
+        }
+    }
+}
+
+/// Cheapest path from `start` to any node satisfying `is_goal`.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone + Hash + Eq,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// `dijkstra` on a caller-provided frontier and node index.
+pub fn dijkstra_with<N, C, I>(
+    frontier: impl Frontier<C>,
+    index: impl NodeIndex<N>,
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar_with(
+        frontier,
+        index,
+        start,
+        successors,
+        |_| C::default(),
+        is_goal,
+    )
+}
+
+/// Like `dijkstra`, but expands nodes in order of cost plus `heuristic`. The
+/// heuristic must never overestimate the remaining cost and must be
+/// consistent, i.e. drop by at most the cost of any edge; closed nodes are
+/// never reopened.
+pub fn astar<N, C, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone + Hash + Eq,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar_with(
+        HeapFrontier::default(),
+        HashIndex::default(),
+        start,
+        successors,
+        heuristic,
+        is_goal,
+    )
+}
+
+/// `astar` on a caller-provided frontier and node index.
+pub fn astar_with<N, C, I>(
+    mut frontier: impl Frontier<C>,
+    index: impl NodeIndex<N>,
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    frontier.push(heuristic(&start), 0);
+    let mut visited = Visited::new(index, start);
+
+    while let Some((_, ix)) = frontier.pop() {
+        let visit = &mut visited.visits[ix];
+        if visit.closed {
+            continue;
+        }
+        visit.closed = true;
+        visited.stats.expanded += 1;
+
+        let (node, cost) = (visit.node.clone(), visit.cost);
+        if is_goal(&node) {
+            return Some(visited.found(ix));
+        }
+        for (next, edge) in successors(&node) {
+            if let Some(next_ix) = visited.relax(next, ix, cost + edge) {
+                let h = heuristic(&visited.visits[next_ix].node);
+                frontier.push(cost + edge + h, next_ix);
+            }
+        }
+    }
+    None
+}
+
+/// Fewest steps from `start` to any node satisfying `is_goal`.
+pub fn bfs<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, usize>>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = Visited::new(HashIndex::default(), start);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(ix) = queue.pop_front() {
+        visited.stats.expanded += 1;
+        let (node, cost) = (visited.visits[ix].node.clone(), visited.visits[ix].cost);
+        if is_goal(&node) {
+            return Some(visited.found(ix));
+        }
+        for next in successors(&node) {
+            if let Some(next_ix) = visited.relax(next, ix, cost + 1) {
+                queue.push_back(next_ix);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{astar, bfs, dijkstra, dijkstra_with, BucketQueue, DenseIndex, Frontier};
+    use crate::input::{AoCInput, Digits};
+    use crate::matrix::{Mat, MatGet};
+
+    const GRID: &str = "1163\n1381\n2136\n3694\n";
+
+    #[test]
+    fn test_grid_paths() {
+        let Digits(mat) = Digits::<Mat<u8>>::from_input(GRID);
+        let goal = (3, 3);
+        let succ = |&ix: &(usize, usize)| {
+            mat.iter_bordering(ix)
+                .map(|(next, risk)| (next, risk as u32))
+        };
+        let manhattan = |&(i, j): &(usize, usize)| (6 - i - j) as u32;
+
+        let found = dijkstra((0, 0), succ, |&ix| ix == goal).unwrap();
+        assert_eq!(found.cost, 17);
+        assert_eq!(found.path.first(), Some(&(0, 0)));
+        assert_eq!(found.path.last(), Some(&goal));
+        let risk: u32 = found.path[1..].iter().map(|&ix| mat[ix] as u32).sum();
+        assert_eq!(risk, found.cost);
+
+        let informed = astar((0, 0), succ, manhattan, |&ix| ix == goal).unwrap();
+        assert_eq!(informed.cost, found.cost);
+        assert!(informed.stats.expanded <= found.stats.expanded);
+
+        // The heuristic is only evaluated for nodes that get pushed.
+        let calls = std::cell::Cell::new(0);
+        let counted = |ix: &(usize, usize)| {
+            calls.set(calls.get() + 1);
+            manhattan(ix)
+        };
+        let informed = astar((0, 0), succ, counted, |&ix| ix == goal).unwrap();
+        assert_eq!(calls.get(), informed.stats.pushed);
+
+        let steps = bfs((0, 0), |&ix| mat.iter_bordering_idx(ix), |&ix| ix == goal).unwrap();
+        assert_eq!(steps.cost, 6);
+        assert_eq!(steps.path.len(), 7);
+
+        let dense = DenseIndex::new(16, |&(i, j): &(usize, usize)| i * 4 + j);
+        let bucketed = dijkstra_with(BucketQueue::default(), dense, (0, 0), succ, |&ix| {
+            ix == goal
+        });
+        let bucketed = bucketed.unwrap();
+        assert_eq!(bucketed.cost, found.cost);
+        assert_eq!(bucketed.stats.discovered, found.stats.discovered);
+
+        let dead_end = |&n: &u8| (n < 5).then_some((n + 1, 1u32));
+        assert_eq!(dijkstra(0, dead_end, |_| false), None);
+    }
+
+    #[test]
+    fn test_bucket_queue() {
+        let mut queue = BucketQueue::default();
+        queue.push(1_000_000u32, 7);
+        assert_eq!(queue.buckets.len(), 1);
+        assert_eq!(queue.pop(), Some((1_000_000, 7)));
+
+        let mut queue = BucketQueue::default();
+        queue.push(3u32, 0);
+        queue.push(1, 1);
+        queue.push(3, 2);
+        assert_eq!(queue.pop(), Some((1, 1)));
+        queue.push(2, 3);
+        queue.push(9, 4);
+        let popped: Vec<(u32, usize)> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(popped, vec![(2, 3), (3, 2), (3, 0), (9, 4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pushed after popping")]
+    fn test_bucket_queue_monotone() {
+        let mut queue = BucketQueue::default();
+        queue.push(5u8, 0);
+        queue.pop();
+        queue.push(4, 1);
+    }
+}
@@ -0,0 +1,496 @@
+// Line segments between lattice points.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::{input::AoCLineInput, pos::Pos};
+
+/// Closed segment from `from` to `to`, both ends included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment<T> {
+    pub from: Pos<T>,
+    pub to: Pos<T>,
+}
+
+// Coordinates are widened to i128, where differences of two coordinates
+// always fit. Products of differences only fit while coordinates stay within
+// about ±2^62, and an intersection point needs one product more, so every
+// multiplication is checked and panics rather than wrapping.
+fn wide<T: Into<i128>>(t: T) -> i128 {
+    t.into()
+}
+
+fn checked(v: Option<i128>) -> i128 {
+    v.expect("segment arithmetic overflows i128")
+}
+
+fn mul(a: i128, b: i128) -> i128 {
+    checked(a.checked_mul(b))
+}
+
+fn narrow<T: TryFrom<i128>>(v: i128) -> T {
+    v.try_into().ok().expect("coordinate out of range")
+}
+
+fn cross((ax, ay): (i128, i128), (bx, by): (i128, i128)) -> i128 {
+    checked(mul(ax, by).checked_sub(mul(ay, bx)))
+}
+
+fn dot((ax, ay): (i128, i128), (bx, by): (i128, i128)) -> i128 {
+    checked(mul(ax, bx).checked_add(mul(ay, by)))
+}
+
+impl<T> Segment<T>
+where
+    T: Copy + Ord + Into<i128> + TryFrom<i128>,
+{
+    pub fn new(from: Pos<T>, to: Pos<T>) -> Self {
+        Self { from, to }
+    }
+
+    fn wide(&self) -> ((i128, i128), (i128, i128)) {
+        (
+            (wide(self.from.x), wide(self.from.y)),
+            (wide(self.to.x), wide(self.to.y)),
+        )
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.from.y == self.to.y
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.from.x == self.to.x
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        self.is_horizontal() || self.is_vertical()
+    }
+
+    /// Whether the segment has a slope of exactly ±1.
+    pub fn is_diagonal(&self) -> bool {
+        let ((x0, y0), (x1, y1)) = self.wide();
+        x0 != x1 && (x1 - x0).abs() == (y1 - y0).abs()
+    }
+
+    /// Smallest and largest corner of the bounding box.
+    pub fn bounding_box(&self) -> (Pos<T>, Pos<T>) {
+        (
+            Pos {
+                x: self.from.x.min(self.to.x),
+                y: self.from.y.min(self.to.y),
+            },
+            Pos {
+                x: self.from.x.max(self.to.x),
+                y: self.from.y.max(self.to.y),
+            },
+        )
+    }
+
+    /// Lattice points from `from` to `to` using Bresenham's algorithm.
+    /// This is exact for axis-aligned and diagonal segments.
+    pub fn points(&self) -> impl Iterator<Item = Pos<T>> {
+        let ((mut x, mut y), (x1, y1)) = self.wide();
+        let (dx, sx) = ((x1 - x).abs(), (x1 - x).signum());
+        let (dy, sy) = (-(y1 - y).abs(), (y1 - y).signum());
+        let mut err = dx + dy;
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let p = Pos {
+                x: narrow(x),
+                y: narrow(y),
+            };
+            if x == x1 && y == y1 {
+                done = true;
+            } else {
+                let e2 = 2 * err;
+                if e2 >= dy {
+                    err += dy;
+                    x += sx;
+                }
+                if e2 <= dx {
+                    err += dx;
+                    y += sy;
+                }
+            }
+            Some(p)
+        })
+    }
+
+    /// Geometric intersection of both segments, if it contains a lattice point.
+    /// Crossing segments give a single point (`from == to`), collinear ones
+    /// their overlap, oriented like `self`.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (p, p1) = self.wide();
+        let (q, q1) = other.wide();
+        let r = (p1.0 - p.0, p1.1 - p.1);
+        let s = (q1.0 - q.0, q1.1 - q.1);
+        let qp = (q.0 - p.0, q.1 - p.1);
+        let denom = cross(r, s);
+
+        let to_pos = |(x, y): (i128, i128)| Pos {
+            x: narrow(x),
+            y: narrow(y),
+        };
+
+        if denom == 0 {
+            if cross(qp, r) != 0 || cross(qp, s) != 0 {
+                // Parallel, but not on the same line.
+                return None;
+            }
+            if r == (0, 0) && s == (0, 0) {
+                return (p == q).then_some(*self);
+            }
+            if r == (0, 0) {
+                return other.contains(&self.from).then_some(*self);
+            }
+
+            // Collinear, project everything onto r.
+            let len = dot(r, r);
+            let t0 = dot(qp, r);
+            let t1 = checked(t0.checked_add(dot(s, r)));
+            let (lo, hi) = (t0.min(t1).max(0), t0.max(t1).min(len));
+            if lo > hi {
+                return None;
+            }
+            let at = |t: i128| {
+                if t == 0 {
+                    p
+                } else if t == len {
+                    p1
+                } else if t == t0 {
+                    q
+                } else {
+                    q1
+                }
+            };
+            return Some(Self::new(to_pos(at(lo)), to_pos(at(hi))));
+        }
+
+        // p + t/denom * r == q + u/denom * s
+        let (t, u) = (cross(qp, s), cross(qp, r));
+        let in_range = |v: i128| {
+            if denom > 0 {
+                0 <= v && v <= denom
+            } else {
+                denom <= v && v <= 0
+            }
+        };
+        if !in_range(t) || !in_range(u) {
+            return None;
+        }
+        let (nx, ny) = (mul(t, r.0), mul(t, r.1));
+        if nx % denom != 0 || ny % denom != 0 {
+            return None;
+        }
+        let pt = to_pos((p.0 + nx / denom, p.1 + ny / denom));
+        Some(Self::new(pt, pt))
+    }
+
+    pub fn contains(&self, pos: &Pos<T>) -> bool {
+        let (p, p1) = self.wide();
+        let q = (wide(pos.x), wide(pos.y));
+        let (r, qp) = ((p1.0 - p.0, p1.1 - p.1), (q.0 - p.0, q.1 - p.1));
+        let along = dot(qp, r);
+        cross(r, qp) == 0 && 0 <= along && along <= dot(r, r)
+    }
+}
+
+impl<T> AoCLineInput for Segment<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Debug,
+{
+    fn from_line(s: &str) -> Self {
+        let (from, to) = s.split_once(" -> ").unwrap();
+        Self {
+            from: Pos::from_line(from),
+            to: Pos::from_line(to),
+        }
+    }
+}
+
+/// The four directions a segment can have for `count_overlaps`.
+/// Each segment lies on a line identified by `(Family, key)` and covers
+/// the parameter range `t` on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Family {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    AntiDiagonal,
+}
+
+impl Family {
+    const ALL: [Family; 4] = [
+        Family::Horizontal,
+        Family::Vertical,
+        Family::Diagonal,
+        Family::AntiDiagonal,
+    ];
+
+    fn of(((x0, y0), (x1, y1)): ((i128, i128), (i128, i128))) -> (Self, i128, (i128, i128)) {
+        let sorted = |a: i128, b: i128| (a.min(b), a.max(b));
+        if y0 == y1 {
+            (Family::Horizontal, y0, sorted(x0, x1))
+        } else if x0 == x1 {
+            (Family::Vertical, x0, sorted(y0, y1))
+        } else if x1 - x0 == y1 - y0 {
+            (Family::Diagonal, x0 - y0, sorted(x0, x1))
+        } else if x1 - x0 == y0 - y1 {
+            (Family::AntiDiagonal, x0 + y0, sorted(x0, x1))
+        } else {
+            panic!("segment is neither axis-aligned nor diagonal");
+        }
+    }
+
+    fn point(&self, key: i128, t: i128) -> (i128, i128) {
+        match self {
+            Family::Horizontal => (t, key),
+            Family::Vertical => (key, t),
+            Family::Diagonal => (t, t - key),
+            Family::AntiDiagonal => (t, key - t),
+        }
+    }
+
+    fn param(&self, (x, y): (i128, i128)) -> (i128, i128) {
+        match self {
+            Family::Horizontal => (y, x),
+            Family::Vertical => (x, y),
+            Family::Diagonal => (x - y, x),
+            Family::AntiDiagonal => (x + y, x),
+        }
+    }
+
+    /// The line through `(x, y)` is `a * x + b * y == key`.
+    fn coefficients(&self) -> (i128, i128) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (1, -1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    /// The lattice point where line `key` of this family meets line
+    /// `other_key` of another family, if there is one.
+    fn meet(&self, key: i128, other: Family, other_key: i128) -> Option<(i128, i128)> {
+        let ((a0, b0), (a1, b1)) = (self.coefficients(), other.coefficients());
+        let det = a0 * b1 - a1 * b0;
+        let (nx, ny) = (key * b1 - other_key * b0, a0 * other_key - a1 * key);
+        (nx % det == 0 && ny % det == 0).then_some((nx / det, ny / det))
+    }
+}
+
+/// A covered range `lo..=hi` on the line `key` of some family.
+type LineRange = (i128, (i128, i128));
+
+/// Lattice points where a covered range of family `a` crosses one of family
+/// `b`. Within `(key of a, key of b)` coordinates every range of `a` spans
+/// an interval of `b` keys at a fixed `a` key and vice versa, so this sweeps
+/// over `a` keys with the active `b` lines kept in a sorted set.
+fn crossings(
+    (a, a_ranges): (Family, &[LineRange]),
+    (b, b_ranges): (Family, &[LineRange]),
+    out: &mut HashSet<(i128, i128)>,
+) {
+    let span = |family: Family, key: i128, (lo, hi): (i128, i128), to: Family| {
+        let (from, to) = (
+            to.param(family.point(key, lo)).0,
+            to.param(family.point(key, hi)).0,
+        );
+        (from.min(to), from.max(to))
+    };
+    // At equal `a` keys, lines open before and close after the queries.
+    let mut events = Vec::new();
+    for &(kb, range) in b_ranges {
+        let (lo, hi) = span(b, kb, range, a);
+        events.push((lo, 0, kb, kb));
+        events.push((hi, 2, kb, kb));
+    }
+    for &(ka, range) in a_ranges {
+        let (lo, hi) = span(a, ka, range, b);
+        events.push((ka, 1, lo, hi));
+    }
+    events.sort_unstable();
+
+    // Covered ranges on one line are disjoint, so a `b` key is active at
+    // most once.
+    let mut active = BTreeSet::new();
+    for (ka, kind, lo, hi) in events {
+        match kind {
+            0 => {
+                active.insert(lo);
+            }
+            1 => out.extend(active.range(lo..=hi).filter_map(|&kb| a.meet(ka, b, kb))),
+            _ => {
+                active.remove(&lo);
+            }
+        }
+    }
+}
+
+/// Sweep over interval endpoints on one line. Returns the ranges covered at
+/// least once and the ranges covered at least twice, both sorted.
+type Ranges = Vec<(i128, i128)>;
+fn sweep(intervals: &[(i128, i128)]) -> (Ranges, Ranges) {
+    let events = intervals
+        .iter()
+        .flat_map(|&(a, b)| [(a, 1), (b + 1, -1)])
+        .sorted()
+        .collect_vec();
+
+    let (mut once, mut twice) = (Vec::new(), Vec::new());
+    let (mut count, mut once_start, mut twice_start) = (0, 0, 0);
+    for (t, delta) in events {
+        let prev = count;
+        count += delta;
+        if prev < 1 && count >= 1 {
+            once_start = t;
+        } else if prev >= 1 && count < 1 {
+            once.push((once_start, t - 1));
+        }
+        if prev < 2 && count >= 2 {
+            twice_start = t;
+        } else if prev >= 2 && count < 2 {
+            twice.push((twice_start, t - 1));
+        }
+    }
+    (once, twice)
+}
+
+fn in_ranges(ranges: &[(i128, i128)], t: i128) -> bool {
+    let i = ranges.partition_point(|&(_, hi)| hi < t);
+    i < ranges.len() && ranges[i].0 <= t
+}
+
+/// Number of lattice points covered by at least two segments. Only supports
+/// horizontal, vertical and diagonal segments. Runs a sweep along every line,
+/// then one across the lines of each pair of directions to find where their
+/// covered ranges cross. For `n` segments with `k` crossings this takes
+/// O((n + k) log n), however long the segments are.
+pub fn count_overlaps<T>(segments: &[Segment<T>]) -> usize
+where
+    T: Copy + Ord + Into<i128> + TryFrom<i128>,
+{
+    let mut lines: HashMap<(Family, i128), Vec<(i128, i128)>> = HashMap::new();
+    for s in segments {
+        let (family, key, range) = Family::of(s.wide());
+        lines.entry((family, key)).or_default().push(range);
+    }
+
+    let swept: HashMap<(Family, i128), (Ranges, Ranges)> = lines
+        .into_iter()
+        .map(|(line, ranges)| (line, sweep(&ranges)))
+        .collect();
+
+    let mut count: i128 = swept
+        .values()
+        .flat_map(|(_, twice)| twice.iter().map(|(a, b)| b - a + 1))
+        .sum();
+
+    // Crossings between different lines are covered twice. They were already
+    // counted once for every line overlap they lie in, which can be zero.
+    let mut covered: HashMap<Family, Vec<LineRange>> = HashMap::new();
+    for (&(family, key), (once, _)) in &swept {
+        let ranges = covered.entry(family).or_default();
+        ranges.extend(once.iter().map(|&range| (key, range)));
+    }
+    let mut points = HashSet::new();
+    for (a, b) in Family::ALL.into_iter().tuple_combinations() {
+        if let (Some(ra), Some(rb)) = (covered.get(&a), covered.get(&b)) {
+            crossings((a, ra.as_slice()), (b, rb.as_slice()), &mut points);
+        }
+    }
+    for pt in points {
+        let overlaps = Family::ALL
+            .into_iter()
+            .filter(|family| {
+                let (key, t) = family.param(pt);
+                swept
+                    .get(&(*family, key))
+                    .is_some_and(|(_, twice)| in_ranges(twice, t))
+            })
+            .count() as i128;
+        count += if overlaps == 0 { 1 } else { 1 - overlaps };
+    }
+    count as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::{count_overlaps, Segment};
+    use crate::pos::Pos;
+    use itertools::Itertools;
+
+    fn seg(x0: i64, y0: i64, x1: i64, y1: i64) -> Segment<i64> {
+        Segment::new(Pos::new(x0, y0), Pos::new(x1, y1))
+    }
+
+    #[test]
+    fn test_points() {
+        let pts = |s: Segment<i64>| s.points().map(|p| (p.x, p.y)).collect_vec();
+        assert_eq!(pts(seg(1, 1, 1, 3)), vec![(1, 1), (1, 2), (1, 3)]);
+        assert_eq!(pts(seg(9, 7, 7, 9)), vec![(9, 7), (8, 8), (7, 9)]);
+        assert_eq!(pts(seg(0, 0, 4, 2)).len(), 5);
+    }
+
+    #[test]
+    fn test_intersection() {
+        assert_eq!(
+            seg(0, 0, 4, 4).intersection(&seg(0, 4, 4, 0)),
+            Some(seg(2, 2, 2, 2))
+        );
+        // Crossing between lattice points.
+        assert_eq!(seg(0, 0, 3, 3).intersection(&seg(0, 3, 3, 0)), None);
+        assert_eq!(
+            seg(0, 0, 10, 0).intersection(&seg(12, 0, 5, 0)),
+            Some(seg(5, 0, 10, 0))
+        );
+        assert_eq!(seg(0, 0, 10, 0).intersection(&seg(0, 1, 10, 1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows i128")]
+    fn test_intersection_overflow() {
+        let (lo, hi) = (i64::MIN, i64::MAX);
+        seg(lo, lo, hi, hi).intersection(&seg(lo, hi, hi, lo));
+    }
+
+    #[test]
+    fn test_count_overlaps_matches_points() {
+        // Short segments in all four directions on a small board, so that
+        // many of them overlap and cross, including between lattice points.
+        let mut state = 12345u64;
+        let mut next = |n: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((state >> 33) % n) as i64
+        };
+        let segs = (0..80)
+            .map(|_| {
+                let (x, y, len) = (next(20), next(20), next(12));
+                let (dx, dy) = [(1, 0), (0, 1), (1, 1), (1, -1)][next(4) as usize];
+                seg(x, y, x + dx * len, y + dy * len)
+            })
+            .collect_vec();
+        let covered = segs.iter().flat_map(|s| s.points()).counts();
+        let want = covered.values().filter(|&&n| n >= 2).count();
+        assert_eq!(count_overlaps(&segs), want);
+    }
+
+    #[test]
+    fn test_count_overlaps_large() {
+        let big = 1_000_000_000_000;
+        let segs = vec![
+            seg(-big, 0, big, 0),
+            seg(0, -big, 0, big),
+            seg(-big, -big, big, big),
+            seg(5, 0, big + 5, 0),
+        ];
+        // [5, big] twice on the x axis, plus the crossing at the origin.
+        assert_eq!(count_overlaps(&segs), big as usize - 5 + 1 + 1);
+    }
+}
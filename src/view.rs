@@ -0,0 +1,271 @@
+// Lazy views over grids. They compute elements on demand from the grid they
+// wrap, so they can be stacked without copying anything.
+use std::marker::PhantomData;
+
+use crate::matrix::{Ix, MatGet, SignedIx};
+
+/// Forward to the viewed grid so views can borrow instead of own it.
+impl<'a, T: 'a, M: MatGet<'a, T>> MatGet<'a, T> for &M {
+    fn width(&self) -> usize {
+        (**self).width()
+    }
+
+    fn height(&self) -> usize {
+        (**self).height()
+    }
+
+    fn get(&self, ix: Ix) -> T {
+        (**self).get(ix)
+    }
+
+    fn get_signed(&self, ix: SignedIx) -> Option<T> {
+        (**self).get_signed(ix)
+    }
+
+    fn offset(&self, ix: Ix, d: SignedIx) -> Option<Ix> {
+        (**self).offset(ix, d)
+    }
+
+    fn step(&self, ix: Ix, dir: impl Into<crate::pos::Dir8>) -> Option<Ix> {
+        (**self).step(ix, dir)
+    }
+}
+
+/// `rows x cols` copies of a grid. Every element is passed through `f`
+/// together with the index of the tile it is in.
+#[derive(Debug, Clone)]
+pub struct Tiled<M, F> {
+    inner: M,
+    rows: usize,
+    cols: usize,
+    f: F,
+}
+
+impl<M, F> Tiled<M, F> {
+    pub fn new(inner: M, rows: usize, cols: usize, f: F) -> Self {
+        Self {
+            inner,
+            rows,
+            cols,
+            f,
+        }
+    }
+}
+
+impl<'a, T: 'a, M: MatGet<'a, T>, F: Fn(T, Ix) -> T> MatGet<'a, T> for Tiled<M, F> {
+    fn width(&self) -> usize {
+        self.inner.width() * self.cols
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height() * self.rows
+    }
+
+    fn get(&self, (i, j): Ix) -> T {
+        let (m, n) = (self.inner.height(), self.inner.width());
+        (self.f)(self.inner.get((i % m, j % n)), (i / m, j / n))
+    }
+}
+
+/// A grid surrounded by a border of `pad` cells holding `fill`.
+#[derive(Debug, Clone)]
+pub struct Padded<M, T> {
+    inner: M,
+    pad: usize,
+    fill: T,
+}
+
+impl<M, T> Padded<M, T> {
+    pub fn new(inner: M, pad: usize, fill: T) -> Self {
+        Self { inner, pad, fill }
+    }
+
+    /// Index into the padded grid of the inner index `ix`.
+    pub fn outer_ix(&self, (i, j): Ix) -> Ix {
+        (i + self.pad, j + self.pad)
+    }
+}
+
+impl<'a, T: Copy + 'a, M: MatGet<'a, T>> MatGet<'a, T> for Padded<M, T> {
+    fn width(&self) -> usize {
+        self.inner.width() + 2 * self.pad
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height() + 2 * self.pad
+    }
+
+    fn get(&self, (i, j): Ix) -> T {
+        let inner = (i.checked_sub(self.pad), j.checked_sub(self.pad));
+        match inner {
+            (Some(i), Some(j)) => self.inner.get_checked((i, j)).unwrap_or(self.fill),
+            _ => self.fill,
+        }
+    }
+}
+
+/// A grid with `f` applied to every element.
+pub struct Mapped<M, F, T> {
+    inner: M,
+    f: F,
+    from: PhantomData<fn(T)>,
+}
+
+impl<M, F, T> Mapped<M, F, T> {
+    pub fn new(inner: M, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            from: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'a, U: 'a, M: MatGet<'a, T>, F: Fn(T) -> U> MatGet<'a, U> for Mapped<M, F, T> {
+    fn width(&self) -> usize {
+        self.inner.width()
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height()
+    }
+
+    fn get(&self, ix: Ix) -> U {
+        (self.f)(self.inner.get(ix))
+    }
+}
+
+/// The `height x width` rectangle of a grid whose top left corner is at `origin`.
+#[derive(Debug, Clone, Copy)]
+pub struct SubView<M> {
+    inner: M,
+    origin: Ix,
+    height: usize,
+    width: usize,
+}
+
+impl<M> SubView<M> {
+    pub fn new<'a, T: 'a>(inner: M, origin: Ix, height: usize, width: usize) -> Self
+    where
+        M: MatGet<'a, T>,
+    {
+        assert!(
+            origin.0 + height <= inner.height() && origin.1 + width <= inner.width(),
+            "{}x{} view at {:?} does not fit into {}x{} grid",
+            height,
+            width,
+            origin,
+            inner.height(),
+            inner.width()
+        );
+        Self {
+            inner,
+            origin,
+            height,
+            width,
+        }
+    }
+
+    /// Index into the viewed grid of the view index `ix`.
+    pub fn inner_ix(&self, (i, j): Ix) -> Ix {
+        (self.origin.0 + i, self.origin.1 + j)
+    }
+}
+
+impl<'a, T: 'a, M: MatGet<'a, T>> MatGet<'a, T> for SubView<M> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, ix: Ix) -> T {
+        self.inner.get(self.inner_ix(ix))
+    }
+}
+
+/// Grid of all `k x k` windows of a grid. The window at `(i, j)` has its top
+/// left corner at `(i, j)`; wrap the grid in `Padded` to get one window
+/// centered on every cell instead.
+pub struct Windowed<'m, M, T> {
+    inner: &'m M,
+    k: usize,
+    el: PhantomData<fn() -> T>,
+}
+
+impl<'m, M, T> Windowed<'m, M, T> {
+    pub fn new(inner: &'m M, k: usize) -> Self {
+        assert!(k > 0, "empty window");
+        Self {
+            inner,
+            k,
+            el: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: 'a, M: MatGet<'a, T>> MatGet<'a, SubView<&'a M>> for Windowed<'a, M, T> {
+    fn width(&self) -> usize {
+        (self.inner.width() + 1).saturating_sub(self.k)
+    }
+
+    fn height(&self) -> usize {
+        (self.inner.height() + 1).saturating_sub(self.k)
+    }
+
+    fn get(&self, ix: Ix) -> SubView<&'a M> {
+        SubView::new(self.inner, ix, self.k, self.k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Mapped, Padded, SubView, Tiled, Windowed};
+    use crate::input::{AoCInput, Digits};
+    use crate::matrix::{Mat, MatGet};
+    use itertools::Itertools;
+
+    fn mat() -> Mat<u8> {
+        Digits::<Mat<u8>>::from_input("123\n456\n").0
+    }
+
+    #[test]
+    fn test_tiled_and_mapped() {
+        let mat = mat();
+        let tiled = Tiled::new(&mat, 2, 2, |x, (ti, tj)| x + 10 * (ti + 2 * tj) as u8);
+        assert_eq!((tiled.height(), tiled.width()), (4, 6));
+        assert_eq!(tiled.get((3, 4)), 35);
+        let mapped = Mapped::new(&tiled, |x| x % 2 == 0);
+        assert!(mapped.get((1, 0)));
+        assert_eq!(
+            mapped.iter_bordering_el((0, 2)).collect_vec(),
+            vec![false, true, true]
+        );
+    }
+
+    #[test]
+    fn test_padded_sub_view() {
+        let mat = mat();
+        let padded = Padded::new(&mat, 1, 0);
+        assert_eq!((padded.height(), padded.width()), (4, 5));
+        assert_eq!(padded.get(padded.outer_ix((1, 2))), 6);
+        assert_eq!(padded.iter_bordering_el((0, 0)).sum::<u8>(), 0);
+        let sub = SubView::new(&padded, (1, 1), 2, 2);
+        assert_eq!(sub.iter().map(|(_, x)| x).collect_vec(), vec![1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_windowed() {
+        let mat = mat();
+        let padded = Padded::new(&mat, 1, 0);
+        let windows = Windowed::new(&padded, 3);
+        assert_eq!((windows.height(), windows.width()), (2, 3));
+        let sums = windows
+            .iter()
+            .map(|(_, w)| w.iter().map(|(_, x)| x as u32).sum::<u32>())
+            .collect_vec();
+        assert_eq!(sums, vec![12, 21, 16, 12, 21, 16]);
+    }
+}